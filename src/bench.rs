@@ -0,0 +1,23 @@
+use std::{fmt, time::Instant};
+
+use crate::{day3, day5, day6};
+
+// Benchmarks over generated inputs, run with `cargo run --release -- bench`. Each one times the old and new
+// approaches on the same data and fails if they disagree
+
+pub fn timed<T: fmt::Debug>(label: &str, run: impl FnOnce() -> T) -> T { // Prints how long run took next to what it returned
+    let start = Instant::now();
+    let result = run();
+    println!("{:<14}{:>12?}  ({:?})", label, start.elapsed(), result);
+    result
+}
+
+pub fn run_all() -> Result<(), &'static str> {
+    if cfg!(debug_assertions) {
+        println!("Built without --release, timings won't mean much");
+    }
+    day3::benchmark_masks(100_000, 24)?;
+    day5::benchmark_block_moves(9, 1_000_000, 1_000)?;
+    day6::benchmark_markers(8_000_000, 14)?;
+    Ok(())
+}
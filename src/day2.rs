@@ -1,15 +1,38 @@
 use std::{fs, str::FromStr};
 
+use rules::{Rules, RulesError, ResponseEncoding};
+
+pub mod rules;
+pub mod solver;
+pub mod tournament;
+
 const PUZZLE_INPUT: &str = "data/rps.txt";
 
-enum Shape {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shape {
     Rock = 1,
     Paper = 2,
     Scissors = 3,
 }
 
-#[derive(Clone, Copy)]
-enum Outcome {
+impl Shape {
+    pub const ALL: [Shape; 3] = [Shape::Rock, Shape::Paper, Shape::Scissors];
+
+    pub fn index(&self) -> usize { // Position in Shape::ALL
+        *self as usize - 1
+    }
+
+    pub fn play(&self, opponent: Shape) -> Outcome { // Outcome from the point of view of self
+        match (self.index() + 3 - opponent.index()) % 3 {
+            0 => Outcome::Draw,
+            1 => Outcome::Win, // Each shape beats the one before it
+            _ => Outcome::Lose
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
     Lose = 0,
     Draw = 3,
     Win = 6
@@ -20,6 +43,10 @@ struct Round {
     desired_outcome: Outcome
 }
 
+pub fn score_against(player: Shape, opponent: Shape) -> u32 { // Score for playing a shape, via the outcome it gives
+    Round { opponents_shape: opponent, desired_outcome: player.play(opponent) }.resolve_score()
+}
+
 impl Round {
     fn resolve_score(&self) -> u32 {
         let shape_score = match self.opponents_shape {
@@ -84,6 +111,12 @@ pub fn get_final_score() -> u32 {
     score
 }
 
+pub fn get_final_score_with_rules(rules_path: &str, encoding: ResponseEncoding) -> Result<u32, RulesError> { // e.g. "data/rpsls_rules.txt"
+    let rules = Rules::from_file(rules_path)?;
+    let game_data = fs::read_to_string(PUZZLE_INPUT).expect("Can't read file");
+    rules.score_guide(&game_data, encoding)
+}
+
 // fn resolve_match_points(round: &str) -> u32 {
 //     match round {
 //         "A X" => 4,
@@ -0,0 +1,290 @@
+use std::{fs, collections::HashMap, str::FromStr};
+
+// Rules engine for any odd sized cyclic hand game (rps, rpsls, 7 way etc.)
+// Shapes are referred to by their index in Rules::shapes
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Lose,
+    Draw,
+    Win
+}
+
+#[derive(Clone, Debug)]
+pub struct ShapeDef {
+    pub name: String,
+    pub score: u32,
+    pub opponent_code: String,
+    pub player_code: Option<String>
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResponseEncoding { // How the second column of a strategy guide is read
+    Shape,  // Part 1, the shape to play
+    Outcome // Part 2, the outcome to aim for
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RulesError {
+    InvalidLine(usize, String),
+    UnknownShape(String),
+    DuplicateShape(String),
+    DuplicateCode(String),
+    EvenShapeCount(usize),
+    TooFewShapes(usize),       // Under 3 shapes, so some outcome can't be played for
+    NotCyclic(String, String), // Pair of shapes where neither (or both) beats the other
+    Unbalanced(String),        // Shape that doesn't beat exactly half of the others
+    MissingOutcome(Outcome),
+    UnknownCode(String)
+}
+
+#[derive(Clone, Debug)]
+pub struct Rules {
+    pub shapes: Vec<ShapeDef>,
+    beats: Vec<Vec<bool>>, // beats[a][b] == true when shape a beats shape b
+    outcome_scores: HashMap<Outcome, u32>,
+    outcome_codes: HashMap<String, Outcome>
+}
+
+fn letter_code(index: usize, first: u8) -> String { // Spreadsheet style column names, A..Z then AA, AB.. so codes never run out
+    let mut code = Vec::<u8>::new();
+    let mut rest = index + 1;
+    while rest > 0 {
+        rest -= 1;
+        code.push(first + (rest % 26) as u8);
+        rest /= 26;
+    }
+    code.iter().rev().map(|&c| c as char).collect()
+}
+
+impl Rules {
+    pub fn from_file(path: &str) -> Result<Rules, RulesError> {
+        let data = fs::read_to_string(path).expect("Can't read file");
+        Rules::from_str(&data)
+    }
+
+    pub fn cyclic(n: usize) -> Result<Rules, RulesError> { // Generated n shape game, shape i beats the (n - 1) / 2 shapes before it
+        if n.is_multiple_of(2) {
+            return Err(RulesError::EvenShapeCount(n))
+        }
+        if n < 3 {
+            return Err(RulesError::TooFewShapes(n))
+        }
+        let shapes = (0..n).map(|i| ShapeDef {
+            name: format!("Shape{}", i + 1),
+            score: i as u32 + 1,
+            opponent_code: letter_code(i, b'A'),
+            player_code: Some(letter_code(i, b'a'))
+        }).collect::<Vec<ShapeDef>>();
+
+        let mut beats = vec![vec![false; n]; n];
+        for (i, row) in beats.iter_mut().enumerate() {
+            for step in 1 ..= (n - 1) / 2 {
+                row[(i + n - step) % n] = true;
+            }
+        }
+
+        let outcome_scores = HashMap::from([(Outcome::Lose, 0), (Outcome::Draw, 3), (Outcome::Win, 6)]);
+        let outcome_codes = HashMap::from([
+            ("X".to_string(), Outcome::Lose),
+            ("Y".to_string(), Outcome::Draw),
+            ("Z".to_string(), Outcome::Win)
+        ]);
+
+        let rules = Rules { shapes, beats, outcome_scores, outcome_codes };
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    fn shape_index(&self, name: &str) -> Result<usize, RulesError> {
+        self.shapes
+            .iter()
+            .position(|s| s.name == name)
+            .ok_or_else(|| RulesError::UnknownShape(name.to_string()))
+    }
+
+    fn validate(&self) -> Result<(), RulesError> {
+        let n = self.shapes.len();
+        if n.is_multiple_of(2) {
+            return Err(RulesError::EvenShapeCount(n))
+        }
+        if n < 3 {
+            return Err(RulesError::TooFewShapes(n))
+        }
+
+        for a in 0..n {
+            for b in a + 1 .. n {
+                if self.beats[a][b] == self.beats[b][a] { // Exactly one of each pair must win
+                    return Err(RulesError::NotCyclic(self.shapes[a].name.clone(), self.shapes[b].name.clone()))
+                }
+            }
+            if self.beats[a][a] || self.beats[a].iter().filter(|&&b| b).count() != (n - 1) / 2 {
+                return Err(RulesError::Unbalanced(self.shapes[a].name.clone()))
+            }
+        }
+
+        for outcome in [Outcome::Lose, Outcome::Draw, Outcome::Win] {
+            if !self.outcome_scores.contains_key(&outcome) {
+                return Err(RulesError::MissingOutcome(outcome))
+            }
+        }
+        Ok(())
+    }
+
+    pub fn play(&self, player: usize, opponent: usize) -> Outcome { // Outcome from the players point of view
+        if player == opponent {
+            Outcome::Draw
+        }
+        else if self.beats[player][opponent] {
+            Outcome::Win
+        }
+        else {
+            Outcome::Lose
+        }
+    }
+
+    pub fn outcome_score(&self, outcome: Outcome) -> u32 {
+        self.outcome_scores[&outcome]
+    }
+
+    pub fn score(&self, player: usize, opponent: usize) -> u32 {
+        self.shapes[player].score + self.outcome_score(self.play(player, opponent))
+    }
+
+    pub fn response_for(&self, opponent: usize, outcome: Outcome) -> usize { // Highest scoring shape giving the desired outcome
+        (0..self.shapes.len())
+            .filter(|&shape| self.play(shape, opponent) == outcome)
+            .max_by_key(|&shape| self.shapes[shape].score)
+            .unwrap() // Always exists for a validated cyclic game, every shape beats and loses to at least one other
+    }
+
+    pub fn decode_opponent(&self, code: &str) -> Result<usize, RulesError> {
+        self.shapes
+            .iter()
+            .position(|s| s.opponent_code == code)
+            .ok_or_else(|| RulesError::UnknownCode(code.to_string()))
+    }
+
+    pub fn decode_player(&self, code: &str) -> Result<usize, RulesError> {
+        self.shapes
+            .iter()
+            .position(|s| s.player_code.as_deref() == Some(code))
+            .ok_or_else(|| RulesError::UnknownCode(code.to_string()))
+    }
+
+    pub fn decode_outcome(&self, code: &str) -> Result<Outcome, RulesError> {
+        self.outcome_codes
+            .get(code)
+            .copied()
+            .ok_or_else(|| RulesError::UnknownCode(code.to_string()))
+    }
+
+    pub fn score_line(&self, line: &str, encoding: ResponseEncoding) -> Result<u32, RulesError> { // "A Y" -> score for that round
+        let (opponent_code, response_code) = line
+            .split_once(' ')
+            .ok_or_else(|| RulesError::UnknownCode(line.to_string()))?;
+        let opponent = self.decode_opponent(opponent_code)?;
+        let player = match encoding {
+            ResponseEncoding::Shape => self.decode_player(response_code)?,
+            ResponseEncoding::Outcome => self.response_for(opponent, self.decode_outcome(response_code)?)
+        };
+        Ok(self.score(player, opponent))
+    }
+
+    pub fn score_guide(&self, data: &str, encoding: ResponseEncoding) -> Result<u32, RulesError> {
+        data.split('\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| self.score_line(line, encoding))
+            .sum()
+    }
+}
+
+impl FromStr for Rules {
+    type Err = RulesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut shapes = Vec::<ShapeDef>::new();
+        let mut beats_lines = Vec::<(String, Vec<String>)>::new();
+        let mut outcome_scores = HashMap::<Outcome, u32>::new();
+        let mut outcome_codes = HashMap::<String, Outcome>::new();
+
+        for (line_no, line) in s.split('\n').enumerate() {
+            let words = line.split_whitespace().collect::<Vec<&str>>();
+            if words.is_empty() || words[0].starts_with('#') { // Blank lines and comments
+                continue
+            }
+            let invalid = || RulesError::InvalidLine(line_no + 1, line.to_string());
+
+            match words[0] {
+                "shape" => { // shape <name> <score> <opponent code> [player code]
+                    if words.len() < 4 || words.len() > 5 {
+                        return Err(invalid())
+                    }
+                    if shapes.iter().any(|s| s.name == words[1]) {
+                        return Err(RulesError::DuplicateShape(words[1].to_string()))
+                    }
+                    let score = words[2].parse::<u32>().map_err(|_| invalid())?;
+                    shapes.push(ShapeDef {
+                        name: words[1].to_string(),
+                        score,
+                        opponent_code: words[3].to_string(),
+                        player_code: words.get(4).map(|c| c.to_string())
+                    });
+                }
+                "beats" => { // beats <winner> <losers...>
+                    if words.len() < 3 {
+                        return Err(invalid())
+                    }
+                    beats_lines.push((words[1].to_string(), words[2..].iter().map(|w| w.to_string()).collect()));
+                }
+                "outcome" => { // outcome <lose|draw|win> <score> [code]
+                    if words.len() < 3 || words.len() > 4 {
+                        return Err(invalid())
+                    }
+                    let outcome = match words[1] {
+                        "lose" => Outcome::Lose,
+                        "draw" => Outcome::Draw,
+                        "win" => Outcome::Win,
+                        _ => return Err(invalid())
+                    };
+                    outcome_scores.insert(outcome, words[2].parse::<u32>().map_err(|_| invalid())?);
+                    if let Some(code) = words.get(3) {
+                        if outcome_codes.insert(code.to_string(), outcome).is_some() {
+                            return Err(RulesError::DuplicateCode(code.to_string()))
+                        }
+                    }
+                }
+                _ => return Err(invalid())
+            }
+        }
+
+        for (i, shape) in shapes.iter().enumerate() { // Codes have to decode to a single shape
+            for other in &shapes[i + 1 ..] {
+                if other.opponent_code == shape.opponent_code {
+                    return Err(RulesError::DuplicateCode(shape.opponent_code.clone()))
+                }
+                if shape.player_code.is_some() && other.player_code == shape.player_code {
+                    return Err(RulesError::DuplicateCode(shape.player_code.clone().unwrap()))
+                }
+            }
+        }
+
+        let mut rules = Rules {
+            beats: vec![vec![false; shapes.len()]; shapes.len()],
+            shapes,
+            outcome_scores,
+            outcome_codes
+        };
+
+        for (winner, losers) in beats_lines {
+            let winner = rules.shape_index(&winner)?;
+            for loser in losers {
+                let loser = rules.shape_index(&loser)?;
+                rules.beats[winner][loser] = true;
+            }
+        }
+
+        rules.validate()?;
+        Ok(rules)
+    }
+}
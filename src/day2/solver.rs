@@ -0,0 +1,177 @@
+use std::fs;
+
+use super::{Shape, Outcome, score_against, PUZZLE_INPUT};
+
+// Strategies are scored per round against the opponent, so only the number of each opponent shape matters,
+// not the order they were played in
+
+#[derive(Clone, Copy, Debug)]
+pub enum OpponentModel {
+    Known([usize; 3]), // Exact count of each opponent shape (indexed as Shape::ALL)
+    Random { probabilities: [f64; 3], rounds: usize } // Opponent picks each round independently
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Strategy {
+    Fixed(Shape),     // Always play the same shape
+    PerRoundOptimal,  // See the opponents shape and play the best response to it
+    Mixed([f64; 3])   // Play each shape with a fixed probability, ignoring the opponent
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct StrategyReport {
+    pub strategy: Strategy,
+    pub expected_score: f64,
+    pub variance: f64
+}
+
+#[derive(Clone, Debug)]
+pub struct ConstrainedPlan {
+    pub responses: Vec<(Shape, Shape, usize)>, // (opponent shape, response, number of rounds)
+    pub total_score: u32
+}
+
+impl Strategy {
+    fn response_distribution(&self, opponent: Shape) -> [f64; 3] { // Probability of playing each shape against an opponent shape
+        match self {
+            Strategy::Fixed(shape) => {
+                let mut probabilities = [0.0; 3];
+                probabilities[shape.index()] = 1.0;
+                probabilities
+            }
+            Strategy::PerRoundOptimal => Strategy::Fixed(best_response(opponent)).response_distribution(opponent),
+            Strategy::Mixed(probabilities) => *probabilities
+        }
+    }
+
+    fn round_moments(&self, opponent: Shape) -> (f64, f64) { // (E[score], E[score^2]) for one round against a known shape
+        let probabilities = self.response_distribution(opponent);
+        Shape::ALL
+            .iter()
+            .fold((0.0, 0.0), |(mean, square), player| {
+                let score = score_against(*player, opponent) as f64;
+                let p = probabilities[player.index()];
+                (mean + p * score, square + p * score * score)
+            })
+    }
+
+    pub fn evaluate(&self, model: &OpponentModel) -> StrategyReport {
+        let (expected_score, variance) = match model {
+            OpponentModel::Known(counts) => { // Rounds are independent, so means and variances add
+                Shape::ALL
+                    .iter()
+                    .fold((0.0, 0.0), |(mean, variance), opponent| {
+                        let (round_mean, round_square) = self.round_moments(*opponent);
+                        let count = counts[opponent.index()] as f64;
+                        (mean + count * round_mean, variance + count * (round_square - round_mean * round_mean))
+                    })
+            }
+            OpponentModel::Random { probabilities, rounds } => { // Both players randomness goes into a single round distribution
+                let (mean, square) = Shape::ALL
+                    .iter()
+                    .fold((0.0, 0.0), |(mean, square), opponent| {
+                        let (round_mean, round_square) = self.round_moments(*opponent);
+                        let q = probabilities[opponent.index()];
+                        (mean + q * round_mean, square + q * round_square)
+                    });
+                (*rounds as f64 * mean, *rounds as f64 * (square - mean * mean))
+            }
+        };
+        StrategyReport { strategy: *self, expected_score, variance }
+    }
+}
+
+pub fn best_response(opponent: Shape) -> Shape {
+    *Shape::ALL
+        .iter()
+        .max_by_key(|player| score_against(**player, opponent))
+        .unwrap()
+}
+
+pub fn best_fixed(model: &OpponentModel) -> StrategyReport {
+    Shape::ALL
+        .iter()
+        .map(|shape| Strategy::Fixed(*shape).evaluate(model))
+        .max_by(|a, b| a.expected_score.total_cmp(&b.expected_score))
+        .unwrap()
+}
+
+// The expected score of a mixed strategy is linear in its probabilities, so the best one always puts all
+// of its weight on the best fixed shape. Returned as a Mixed strategy so it can be compared with others
+pub fn best_mixed(model: &OpponentModel) -> StrategyReport {
+    let probabilities = best_fixed(model).strategy.response_distribution(Shape::Rock); // Fixed ignores the opponent
+    Strategy::Mixed(probabilities).evaluate(model)
+}
+
+pub fn compare_strategies(model: &OpponentModel) -> Vec<StrategyReport> {
+    let mut reports = vec![
+        best_fixed(model),
+        Strategy::PerRoundOptimal.evaluate(model),
+        Strategy::Mixed([1.0 / 3.0; 3]).evaluate(model),
+    ];
+    reports.sort_by(|a, b| b.expected_score.total_cmp(&a.expected_score));
+    reports
+}
+
+// Best plan that wins exactly k rounds. Winning some rounds costs more than others, so give the wins
+// to the opponent shapes where winning gains the most over the best draw/loss
+pub fn best_with_exact_wins(counts: [usize; 3], k: usize) -> Result<ConstrainedPlan, &'static str> {
+    if k > counts.iter().sum::<usize>() {
+        return Err("Not enough rounds to win that many")
+    }
+
+    let response_for = |opponent: Shape, win: bool| -> Shape {
+        *Shape::ALL
+            .iter()
+            .filter(|player| (player.play(opponent) == Outcome::Win) == win)
+            .max_by_key(|player| score_against(**player, opponent))
+            .unwrap()
+    };
+
+    let mut opponents = Shape::ALL.to_vec();
+    opponents.sort_by_key(|opponent| {
+        let gain = score_against(response_for(*opponent, true), *opponent) as i64
+            - score_against(response_for(*opponent, false), *opponent) as i64;
+        -gain
+    });
+
+    let mut wins_left = k;
+    let mut responses = Vec::<(Shape, Shape, usize)>::new();
+    for opponent in opponents {
+        let count = counts[opponent.index()];
+        let wins = count.min(wins_left);
+        wins_left -= wins;
+        if wins > 0 {
+            responses.push((opponent, response_for(opponent, true), wins));
+        }
+        if count > wins {
+            responses.push((opponent, response_for(opponent, false), count - wins));
+        }
+    }
+
+    let total_score = responses
+        .iter()
+        .map(|(opponent, player, count)| score_against(*player, *opponent) * *count as u32)
+        .sum::<u32>();
+
+    Ok(ConstrainedPlan { responses, total_score })
+}
+
+pub fn get_opponent_counts() -> [usize; 3] { // Only the first column of the strategy guide is used
+    let game_data = fs::read_to_string(PUZZLE_INPUT).expect("Can't read file");
+    let mut counts = [0; 3];
+    game_data
+        .split('\n')
+        .for_each(|line| match line.chars().next() {
+            Some('A') => counts[Shape::Rock.index()] += 1,
+            Some('B') => counts[Shape::Paper.index()] += 1,
+            Some('C') => counts[Shape::Scissors.index()] += 1,
+            _ => ()
+        });
+    counts
+}
+
+pub fn get_distribution(counts: [usize; 3]) -> [f64; 3] {
+    let total = counts.iter().sum::<usize>().max(1) as f64;
+    counts.map(|count| count as f64 / total)
+}
@@ -0,0 +1,203 @@
+use std::fmt;
+
+use super::{Shape, Outcome, score_against};
+use super::solver::best_response;
+use crate::rng::Rng;
+
+// Round robin tournaments between scripted rock paper scissors bots
+
+#[derive(Clone, Debug)]
+pub enum Bot {
+    Constant(Shape),
+    Cycle(Vec<Shape>),      // Plays the shapes in order, wrapping around
+    CopyLastOpponent(Shape), // Opens with the given shape, then repeats whatever the opponent just played
+    FrequencyCounter,        // Best response to the opponents most played shape so far
+    Random(u64)              // Seeded, so tournaments can be replayed
+}
+
+#[derive(Clone, Debug)]
+pub struct Player {
+    pub name: String,
+    pub bot: Bot
+}
+
+impl Bot {
+    pub fn cycle(shapes: Vec<Shape>) -> Result<Bot, &'static str> {
+        if shapes.is_empty() {
+            return Err("Cycle needs at least one shape")
+        }
+        Ok(Bot::Cycle(shapes))
+    }
+}
+
+impl Player {
+    pub fn new(name: &str, bot: Bot) -> Player {
+        Player { name: name.to_string(), bot }
+    }
+}
+
+struct BotState<'a> { // Per match state, bots start fresh against every opponent
+    bot: &'a Bot,
+    rng: Rng,
+    opponent_counts: [usize; 3]
+}
+
+impl<'a> BotState<'a> {
+    fn new(bot: &'a Bot) -> BotState<'a> {
+        let seed = if let Bot::Random(seed) = bot { *seed } else { 0 };
+        BotState { bot, rng: Rng::new(seed), opponent_counts: [0; 3] }
+    }
+
+    fn choose(&mut self, round: usize, last_opponent: Option<Shape>) -> Shape {
+        match self.bot {
+            Bot::Constant(shape) => *shape,
+            Bot::Cycle(shapes) => shapes[round % shapes.len()],
+            Bot::CopyLastOpponent(opening) => last_opponent.unwrap_or(*opening),
+            Bot::FrequencyCounter => {
+                let most_played = Shape::ALL
+                    .iter()
+                    .rev() // Ties go to rock
+                    .max_by_key(|shape| self.opponent_counts[shape.index()])
+                    .unwrap();
+                best_response(*most_played)
+            }
+            Bot::Random(_) => Shape::ALL[(self.rng.next() % 3) as usize]
+        }
+    }
+
+    fn observe(&mut self, opponent: Shape) {
+        self.opponent_counts[opponent.index()] += 1;
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchResult {
+    pub players: (usize, usize), // Indexes into Tournament::players
+    pub scores: (u32, u32),
+    pub round_wins: (usize, usize),
+    pub draws: usize
+}
+
+impl MatchResult {
+    fn winner(&self) -> Option<usize> { // Matches are decided on score
+        if self.scores.0 > self.scores.1 {
+            Some(self.players.0)
+        }
+        else if self.scores.1 > self.scores.0 {
+            Some(self.players.1)
+        }
+        else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Standing {
+    pub player: usize,
+    pub total_score: u32,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize
+}
+
+pub struct Tournament {
+    pub players: Vec<Player>,
+    pub rounds: usize,
+    pub matches: Vec<MatchResult>
+}
+
+fn play_match(a: &Bot, b: &Bot, rounds: usize) -> (u32, u32, usize, usize, usize) {
+    let (mut state_a, mut state_b) = (BotState::new(a), BotState::new(b));
+    let (mut last_a, mut last_b) = (None, None);
+    let (mut score_a, mut score_b, mut wins_a, mut wins_b, mut draws) = (0, 0, 0, 0, 0);
+
+    for round in 0..rounds {
+        let shape_a = state_a.choose(round, last_b);
+        let shape_b = state_b.choose(round, last_a);
+        score_a += score_against(shape_a, shape_b);
+        score_b += score_against(shape_b, shape_a);
+        match shape_a.play(shape_b) {
+            Outcome::Win => wins_a += 1,
+            Outcome::Lose => wins_b += 1,
+            Outcome::Draw => draws += 1
+        }
+        state_a.observe(shape_b);
+        state_b.observe(shape_a);
+        (last_a, last_b) = (Some(shape_a), Some(shape_b));
+    }
+    (score_a, score_b, wins_a, wins_b, draws)
+}
+
+impl Tournament {
+    pub fn round_robin(players: Vec<Player>, rounds: usize) -> Result<Tournament, &'static str> {
+        if players.iter().any(|player| matches!(&player.bot, Bot::Cycle(shapes) if shapes.is_empty())) {
+            return Err("Cycle needs at least one shape")
+        }
+        let mut matches = Vec::<MatchResult>::new();
+        for a in 0..players.len() {
+            for b in a + 1 .. players.len() {
+                let (score_a, score_b, wins_a, wins_b, draws) = play_match(&players[a].bot, &players[b].bot, rounds);
+                matches.push(MatchResult { players: (a, b), scores: (score_a, score_b), round_wins: (wins_a, wins_b), draws });
+            }
+        }
+        Ok(Tournament { players, rounds, matches })
+    }
+
+    pub fn leaderboard(&self) -> Vec<Standing> { // Sorted by total score, then match wins
+        let mut standings = (0..self.players.len())
+            .map(|player| Standing { player, ..Default::default() })
+            .collect::<Vec<Standing>>();
+
+        for result in &self.matches {
+            standings[result.players.0].total_score += result.scores.0;
+            standings[result.players.1].total_score += result.scores.1;
+            match result.winner() {
+                Some(winner) => {
+                    let loser = if winner == result.players.0 { result.players.1 } else { result.players.0 };
+                    standings[winner].wins += 1;
+                    standings[loser].losses += 1;
+                }
+                None => {
+                    standings[result.players.0].draws += 1;
+                    standings[result.players.1].draws += 1;
+                }
+            }
+        }
+
+        standings.sort_by(|a, b| b.total_score.cmp(&a.total_score).then(b.wins.cmp(&a.wins)));
+        standings
+    }
+}
+
+impl fmt::Display for Tournament {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name_width = self.players.iter().map(|p| p.name.len()).max().unwrap_or(0).max(6);
+
+        writeln!(f, "Leaderboard ({} rounds per match)", self.rounds)?;
+        writeln!(f, "{:>4}  {:<name_width$}  {:>8}  {:>3}  {:>3}  {:>3}", "#", "Player", "Score", "W", "D", "L")?;
+        for (position, standing) in self.leaderboard().iter().enumerate() {
+            writeln!(f, "{:>4}  {:<name_width$}  {:>8}  {:>3}  {:>3}  {:>3}",
+                position + 1, self.players[standing.player].name, standing.total_score, standing.wins, standing.draws, standing.losses)?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "Matches")?;
+        for result in &self.matches {
+            writeln!(f, "{:<name_width$} vs {:<name_width$}  {:>6} - {:<6}  (rounds won {} - {}, drawn {})",
+                self.players[result.players.0].name, self.players[result.players.1].name,
+                result.scores.0, result.scores.1, result.round_wins.0, result.round_wins.1, result.draws)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn default_players() -> Vec<Player> {
+    vec![
+        Player::new("Rocky", Bot::Constant(Shape::Rock)),
+        Player::new("Cycler", Bot::cycle(Shape::ALL.to_vec()).unwrap()),
+        Player::new("Copycat", Bot::CopyLastOpponent(Shape::Paper)),
+        Player::new("Counter", Bot::FrequencyCounter),
+        Player::new("Dice", Bot::Random(2022)),
+    ]
+}
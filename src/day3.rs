@@ -1,9 +1,34 @@
 use std::{fs, collections::{HashSet, HashMap}};
 
+use alphabet::{ItemAlphabet, ItemError};
+use mask::{ItemMask, MaskedRucksack};
+use crate::{bench::timed, rng::Rng};
+
+pub mod alphabet;
+pub mod grouping;
+pub mod mask;
+pub mod repack;
+
 const PUZZLE_INPUT: &str = "data/backpacks.txt";
 const CHUNK_SIZE: usize = 3;
 
-struct Rucksack {
+#[derive(Debug, PartialEq, Eq)]
+pub enum GroupError {
+    InvalidGroupSize,
+    InvalidRucksack { group: usize, error: ItemError }, // Unknown item or uneven compartments, the error has the line
+    ShortGroup { group: usize, size: usize }, // Last group doesn't have enough rucksacks
+    NoBadge { group: usize },
+    MultipleBadges { group: usize, candidates: Vec<char> }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GroupBadge {
+    pub group: usize,
+    pub badge: char,
+    pub priority: u32
+}
+
+pub struct Rucksack {
     first_component: String,
     second_component: String
 }
@@ -20,6 +45,17 @@ impl Rucksack {
         });
         unique_chars.into_iter().collect()
     }
+
+    pub fn parse(line: &str, line_no: usize, alphabet: &ItemAlphabet) -> Result<Rucksack, ItemError> { // Split on chars rather than bytes so non ascii items work
+        alphabet.validate(line, line_no)?;
+        let item_count = line.chars().count();
+        if !item_count.is_multiple_of(2) {
+            return Err(ItemError::UnevenCompartments { line: line_no })
+        }
+        let (split_at, _) = line.char_indices().nth(item_count / 2).unwrap_or((line.len(), ' '));
+        let (first, second) = line.split_at(split_at);
+        Ok(Rucksack { first_component: first.to_string(), second_component: second.to_string() })
+    }
 }
 
 impl TryFrom<(&str, &str)> for Rucksack {
@@ -49,31 +85,78 @@ fn find_duplicate_items(data: String) -> Vec<char> { // Find commmon char in bot
     }).collect::<Vec<char>>()
 }
 
-fn char_to_priority(c: &char) -> u32 { // Converted to ints
-    if c.is_lowercase() { 
-        *c as u32 - 96 
-    }
-    else { 
-        *c as u32 - 38 
-    }
+fn get_rucksacks(data: String) -> Vec<Rucksack> {
+    get_rucksacks_in(&data, &ItemAlphabet::standard()).unwrap() // todo error handling
 }
 
-fn get_rucksacks(data: String) -> Vec<Rucksack> {
-    data.split('\n').map(|line| 
-        Rucksack::try_from(line.split_at(line.len()/2)).unwrap() // todo error handling
-    ).collect::<Vec<Rucksack>>()
+fn get_rucksacks_in(data: &str, alphabet: &ItemAlphabet) -> Result<Vec<Rucksack>, ItemError> {
+    data.split('\n')
+        .enumerate()
+        .map(|(line_no, line)| Rucksack::parse(line, line_no, alphabet))
+        .collect()
 }
 
 pub fn get_priority_sum_part_one() -> u32 {
     let input = fs::read_to_string(PUZZLE_INPUT).expect("Can't read file");
-    let duplicate_items = find_duplicate_items(input);
-
-    duplicate_items.iter().map(char_to_priority).sum::<u32>()
+    mask::priority_sum_part_one(&input, &ItemAlphabet::standard()).expect("Invalid rucksack")
 }
 
 pub fn get_priority_sum_part_two() -> u32 {
+    let (priority_sum, _badges) = get_priority_sum(CHUNK_SIZE).expect("Invalid elf groups");
+    priority_sum
+}
+
+pub fn get_priority_sum(group_size: usize) -> Result<(u32, Vec<GroupBadge>), Vec<GroupError>> { // Sum of badge priorities for groups of any size
     let input = fs::read_to_string(PUZZLE_INPUT).expect("Can't read file");
-    let rucksack_vec = get_rucksacks(input);
+    priority_sum_in(&input, &ItemAlphabet::standard(), group_size)
+}
+
+pub fn priority_sum_in(data: &str, alphabet: &ItemAlphabet, group_size: usize) -> Result<(u32, Vec<GroupBadge>), Vec<GroupError>> {
+    let (badges, errors): (Vec<_>, Vec<_>) = find_badges_in(data, alphabet, group_size).into_iter().partition(|res| res.is_ok());
+
+    if !errors.is_empty() {
+        return Err(errors.into_iter().filter_map(Result::err).collect())
+    }
+    let badges = badges.into_iter().filter_map(Result::ok).collect::<Vec<GroupBadge>>();
+    Ok((badges.iter().map(|b| b.priority).sum::<u32>(), badges))
+}
+
+pub fn find_badges(data: &str, group_size: usize) -> Vec<Result<GroupBadge, GroupError>> { // One result per group, in input order
+    find_badges_in(data, &ItemAlphabet::standard(), group_size)
+}
+
+pub fn find_badges_in(data: &str, alphabet: &ItemAlphabet, group_size: usize) -> Vec<Result<GroupBadge, GroupError>> {
+    if group_size == 0 {
+        return vec![Err(GroupError::InvalidGroupSize)]
+    }
+
+    data.split('\n')
+        .collect::<Vec<&str>>()
+        .chunks(group_size)
+        .enumerate()
+        .map(|(group, lines)| {
+            if lines.len() < group_size {
+                return Err(GroupError::ShortGroup { group, size: lines.len() })
+            }
+            let common = lines.iter().enumerate().try_fold(ItemMask(u64::MAX), |common, (member, line)| {
+                let rucksack = MaskedRucksack::parse(line, group * group_size + member, alphabet)
+                    .map_err(|error| GroupError::InvalidRucksack { group, error })?;
+                Ok(common & rucksack.items())
+            })?;
+            match common.len() {
+                0 => Err(GroupError::NoBadge { group }),
+                1 => {
+                    let position = common.positions().next().unwrap();
+                    Ok(GroupBadge { group, badge: alphabet.item_at(position).unwrap(), priority: alphabet.priority_at(position).unwrap() })
+                }
+                _ => Err(GroupError::MultipleBadges { group, candidates: common.items(alphabet).collect() })
+            }
+        })
+        .collect()
+}
+
+fn badge_priority_sum(data: String) -> u32 {
+    let rucksack_vec = get_rucksacks(data);
 
     let elf_groups = rucksack_vec.chunks(CHUNK_SIZE);
 
@@ -98,7 +181,57 @@ pub fn get_priority_sum_part_two() -> u32 {
     });
 
     // Get sum of priorities
-    group_badge_chars.iter().map(
-        char_to_priority
+    let alphabet = ItemAlphabet::standard();
+    group_badge_chars.iter().filter_map(|c|
+        alphabet.priority(*c)
     ).sum::<u32>()
-}
\ No newline at end of file
+}
+
+pub fn generate_rucksacks(groups: usize, compartment_size: usize, seed: u64) -> String { // Groups of CHUNK_SIZE with one badge each and one duplicate per rucksack
+    let items = ('a'..='z').chain('A'..='Z').collect::<Vec<char>>();
+    let pool_size = (items.len() - 1) / CHUNK_SIZE; // Each elf gets its own items so only the badge is shared by the group
+    let half_pool = (pool_size - 1) / 2;
+    let mut rng = Rng::new(seed);
+
+    let mut lines = Vec::<String>::with_capacity(groups * CHUNK_SIZE);
+    for _ in 0..groups {
+        let mut shuffled = items.clone();
+        for i in (1..shuffled.len()).rev() {
+            shuffled.swap(i, rng.below(i + 1));
+        }
+        let badge = shuffled[0];
+
+        for elf in 0..CHUNK_SIZE {
+            let pool = &shuffled[1 + elf * pool_size .. 1 + (elf + 1) * pool_size];
+            let duplicate = pool[0];
+            let (first_pool, second_pool) = (&pool[1 .. 1 + half_pool], &pool[1 + half_pool .. 1 + 2 * half_pool]);
+
+            let mut first = vec![badge, duplicate]; // Badge only ever goes in the first compartment
+            let mut second = vec![duplicate];
+            first.extend((2..compartment_size).map(|_| first_pool[rng.below(first_pool.len())]));
+            second.extend((1..compartment_size).map(|_| second_pool[rng.below(second_pool.len())]));
+            for i in (1..compartment_size).rev() {
+                first.swap(i, rng.below(i + 1));
+                second.swap(i, rng.below(i + 1));
+            }
+            lines.push(first.into_iter().chain(second).collect::<String>());
+        }
+    }
+    lines.join("\n")
+}
+
+pub fn benchmark_masks(groups: usize, compartment_size: usize) -> Result<(), &'static str> { // Compare HashSet/String approach against bitmasks on generated input
+    let data = generate_rucksacks(groups, compartment_size, 2022);
+    println!("{} rucksacks, {} items each", groups * CHUNK_SIZE, compartment_size * 2);
+
+    let alphabet = ItemAlphabet::standard();
+    let part_one = timed("Part 1 sets", || find_duplicate_items(data.clone()).iter().filter_map(|c| alphabet.priority(*c)).sum::<u32>());
+    let part_one_masked = timed("Part 1 masks", || mask::priority_sum_part_one(&data, &alphabet));
+    let part_two = timed("Part 2 sets", || badge_priority_sum(data.clone()));
+    let part_two_masked = timed("Part 2 masks", || mask::priority_sum_part_two(&data, &alphabet, CHUNK_SIZE));
+
+    if Ok(part_one) != part_one_masked || Ok(part_two) != part_two_masked {
+        return Err("Bitmask sums don't match the set based ones")
+    }
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use super::mask::ItemMask;
+
+// Configurable mapping from items to priorities. Items can be any chars, including non ascii ones,
+// and anything not in the alphabet is an error instead of a made up priority
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItemError {
+    UnknownItem { item: char, line: usize, position: usize }, // Line and position count from 0
+    UnevenCompartments { line: usize },
+    DuplicateItem(char),
+    TooManyItems(usize) // Alphabet is too big for an ItemMask
+}
+
+#[derive(Debug, Clone)]
+pub struct ItemAlphabet {
+    items: Vec<char>,
+    priorities: Vec<u32>,
+    index: HashMap<char, usize>,     // Item to its position in items
+    ascii_index: [Option<usize>; 128] // Same for ascii items, which are nearly all of them, without hashing
+}
+
+impl ItemAlphabet {
+    pub fn standard() -> ItemAlphabet { // a-z are 1-26, A-Z are 27-52
+        ItemAlphabet::from_ordering(&('a'..='z').chain('A'..='Z').collect::<String>()).unwrap()
+    }
+
+    pub fn from_ordering(items: &str) -> Result<ItemAlphabet, ItemError> { // Priorities go up by one from 1 in the given order
+        ItemAlphabet::from_priorities(items.chars().zip(1..))
+    }
+
+    pub fn from_priorities(pairs: impl IntoIterator<Item = (char, u32)>) -> Result<ItemAlphabet, ItemError> {
+        let mut alphabet = ItemAlphabet { items: Vec::new(), priorities: Vec::new(), index: HashMap::new(), ascii_index: [None; 128] };
+        for (item, priority) in pairs {
+            if alphabet.index.insert(item, alphabet.items.len()).is_some() {
+                return Err(ItemError::DuplicateItem(item))
+            }
+            if item.is_ascii() {
+                alphabet.ascii_index[item as usize] = Some(alphabet.items.len());
+            }
+            alphabet.items.push(item);
+            alphabet.priorities.push(priority);
+        }
+        Ok(alphabet)
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn contains(&self, item: char) -> bool {
+        self.index_of(item).is_some()
+    }
+
+    pub fn index_of(&self, item: char) -> Option<usize> {
+        if item.is_ascii() {
+            self.ascii_index[item as usize]
+        }
+        else {
+            self.index.get(&item).copied()
+        }
+    }
+
+    pub fn priority(&self, item: char) -> Option<u32> {
+        self.index_of(item).map(|i| self.priorities[i])
+    }
+
+    pub fn priority_at(&self, index: usize) -> Option<u32> {
+        self.priorities.get(index).copied()
+    }
+
+    pub fn item_at(&self, index: usize) -> Option<char> {
+        self.items.get(index).copied()
+    }
+
+    pub fn validate(&self, items: &str, line: usize) -> Result<(), ItemError> {
+        match items.chars().enumerate().find(|(_, item)| !self.contains(*item)) {
+            Some((position, item)) => Err(ItemError::UnknownItem { item, line, position }),
+            None => Ok(())
+        }
+    }
+
+    pub fn mask(&self, items: &str, line: usize) -> Result<ItemMask, ItemError> { // Bit n is the item at position n, see ItemMask
+        if self.len() > 64 {
+            return Err(ItemError::TooManyItems(self.len()))
+        }
+        items.chars().enumerate().try_fold(ItemMask::default(), |mask, (position, item)| {
+            match self.index_of(item) {
+                Some(i) => Ok(mask | ItemMask(1 << i)),
+                None => Err(ItemError::UnknownItem { item, line, position })
+            }
+        })
+    }
+}
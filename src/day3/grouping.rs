@@ -0,0 +1,117 @@
+use super::alphabet::ItemAlphabet;
+use super::mask::{ItemMask, get_masked_rucksacks};
+
+// Badge solver for manifests where the elves aren't listed group by group. Backtracking search that always
+// places the first unassigned rucksack, so each partition is only visited once. Stops after finding two
+// partitions since that's enough to know the answer is ambiguous
+
+const DEFAULT_NODE_LIMIT: usize = 1_000_000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BadgeGroup {
+    pub members: Vec<usize>, // Line numbers (from 0) of the rucksacks in the group
+    pub badge: char
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Partition {
+    Unique(Vec<BadgeGroup>),
+    Ambiguous(Vec<BadgeGroup>, Vec<BadgeGroup>), // Two of the valid partitions
+    Impossible,
+    Undecided(Option<Vec<BadgeGroup>>) // Node limit hit, with the partition found so far if there was one
+}
+
+struct Search<'a> {
+    alphabet: &'a ItemAlphabet,
+    items: &'a [ItemMask],
+    group_size: usize,
+    assigned: Vec<bool>,
+    groups: Vec<BadgeGroup>,
+    solutions: Vec<Vec<BadgeGroup>>,
+    nodes: usize,
+    node_limit: usize
+}
+
+impl<'a> Search<'a> {
+    fn finished(&self) -> bool {
+        self.solutions.len() >= 2 || self.nodes >= self.node_limit
+    }
+
+    fn place_next(&mut self) {
+        match self.assigned.iter().position(|a| !a) {
+            None => self.solutions.push(self.groups.clone()), // Everyone has a group
+            Some(first) => {
+                self.assigned[first] = true;
+                self.extend_group(&mut vec![first], self.items[first], first + 1);
+                self.assigned[first] = false;
+            }
+        }
+    }
+
+    fn extend_group(&mut self, members: &mut Vec<usize>, common: ItemMask, start: usize) {
+        if members.len() == self.group_size {
+            if common.len() == 1 {
+                let badge = common.items(self.alphabet).next().unwrap();
+                self.groups.push(BadgeGroup { members: members.clone(), badge });
+                self.place_next();
+                self.groups.pop();
+            }
+            return
+        }
+
+        for next in start .. self.items.len() {
+            if self.finished() {
+                return
+            }
+            if self.assigned[next] {
+                continue
+            }
+            let narrowed = common & self.items[next];
+            if narrowed.is_empty() { // Group can't have a badge anymore
+                continue
+            }
+            self.nodes += 1;
+            self.assigned[next] = true;
+            members.push(next);
+            self.extend_group(members, narrowed, next + 1);
+            members.pop();
+            self.assigned[next] = false;
+        }
+    }
+}
+
+pub fn partition_with_limit(data: &str, alphabet: &ItemAlphabet, group_size: usize, node_limit: usize) -> Result<Partition, &'static str> {
+    let items = get_masked_rucksacks(data, alphabet)
+        .map_err(|_| "Rucksack has an unknown item or uneven compartments")?
+        .iter()
+        .map(|rucksack| rucksack.items())
+        .collect::<Vec<ItemMask>>();
+    if group_size == 0 || items.len() % group_size != 0 {
+        return Err("Rucksacks can't be split evenly into groups of that size")
+    }
+
+    let mut search = Search {
+        alphabet,
+        items: &items,
+        group_size,
+        assigned: vec![false; items.len()],
+        groups: Vec::new(),
+        solutions: Vec::new(),
+        nodes: 0,
+        node_limit
+    };
+    search.place_next();
+
+    let limit_hit = search.nodes >= node_limit && search.solutions.len() < 2;
+    let mut solutions = search.solutions.into_iter();
+    Ok(match (solutions.next(), solutions.next(), limit_hit) {
+        (Some(first), Some(second), _) => Partition::Ambiguous(first, second),
+        (first, _, true) => Partition::Undecided(first),
+        (Some(first), None, false) => Partition::Unique(first),
+        (None, _, false) => Partition::Impossible
+    })
+}
+
+pub fn partition(data: &str, group_size: usize) -> Result<Partition, &'static str> {
+    partition_with_limit(data, &ItemAlphabet::standard(), group_size, DEFAULT_NODE_LIMIT)
+}
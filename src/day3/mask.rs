@@ -0,0 +1,117 @@
+use std::ops::{BitAnd, BitOr};
+
+use super::{Rucksack, GroupError, priority_sum_in};
+use super::alphabet::{ItemAlphabet, ItemError};
+
+// Set of rucksack items as a bitmask, bit n is set when the item at position n of the ItemAlphabet is present.
+// Alphabets of up to 64 items fit in a u64 so set operations are single instructions. Masks are built with
+// ItemAlphabet::mask, and only mean anything alongside the alphabet they were built with
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ItemMask(pub u64);
+
+impl ItemMask {
+    pub fn contains(&self, item: &char, alphabet: &ItemAlphabet) -> bool {
+        alphabet.index_of(*item).is_some_and(|i| i < 64 && self.0 & (1 << i) != 0)
+    }
+
+    pub fn len(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = usize> { // Lowest position first
+        let mut remaining = self.0;
+        std::iter::from_fn(move || {
+            if remaining == 0 {
+                return None
+            }
+            let position = remaining.trailing_zeros() as usize;
+            remaining &= remaining - 1; // clear lowest set bit
+            Some(position)
+        })
+    }
+
+    pub fn items<'a>(&self, alphabet: &'a ItemAlphabet) -> impl Iterator<Item = char> + 'a {
+        self.positions().filter_map(|position| alphabet.item_at(position))
+    }
+
+    pub fn priorities<'a>(&self, alphabet: &'a ItemAlphabet) -> impl Iterator<Item = u32> + 'a { // In alphabet order
+        self.positions().filter_map(|position| alphabet.priority_at(position))
+    }
+
+    pub fn priority_sum(&self, alphabet: &ItemAlphabet) -> u32 {
+        self.priorities(alphabet).sum::<u32>()
+    }
+}
+
+impl BitAnd for ItemMask {
+    type Output = ItemMask;
+
+    fn bitand(self, other: ItemMask) -> ItemMask {
+        ItemMask(self.0 & other.0)
+    }
+}
+
+impl BitOr for ItemMask {
+    type Output = ItemMask;
+
+    fn bitor(self, other: ItemMask) -> ItemMask {
+        ItemMask(self.0 | other.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MaskedRucksack {
+    pub first_component: ItemMask,
+    pub second_component: ItemMask
+}
+
+impl MaskedRucksack {
+    pub fn duplicates(&self) -> ItemMask { // Items in both compartments
+        self.first_component & self.second_component
+    }
+
+    pub fn items(&self) -> ItemMask {
+        self.first_component | self.second_component
+    }
+}
+
+impl MaskedRucksack {
+    pub fn parse(line: &str, line_no: usize, alphabet: &ItemAlphabet) -> Result<MaskedRucksack, ItemError> {
+        let rucksack = Rucksack::parse(line, line_no, alphabet)?;
+        Ok(MaskedRucksack {
+            first_component: alphabet.mask(&rucksack.first_component, line_no)?,
+            second_component: alphabet.mask(&rucksack.second_component, line_no)?
+        })
+    }
+}
+
+impl TryFrom<&str> for MaskedRucksack {
+    type Error = ItemError;
+
+    fn try_from(line: &str) -> Result<Self, Self::Error> { // Standard alphabet
+        MaskedRucksack::parse(line, 0, &ItemAlphabet::standard())
+    }
+}
+
+pub fn get_masked_rucksacks(data: &str, alphabet: &ItemAlphabet) -> Result<Vec<MaskedRucksack>, ItemError> {
+    data.split('\n')
+        .enumerate()
+        .map(|(line_no, line)| MaskedRucksack::parse(line, line_no, alphabet))
+        .collect()
+}
+
+pub fn priority_sum_part_one(data: &str, alphabet: &ItemAlphabet) -> Result<u32, ItemError> {
+    Ok(get_masked_rucksacks(data, alphabet)?
+        .iter()
+        .map(|rucksack| rucksack.duplicates().priority_sum(alphabet))
+        .sum::<u32>())
+}
+
+pub fn priority_sum_part_two(data: &str, alphabet: &ItemAlphabet, group_size: usize) -> Result<u32, Vec<GroupError>> { // Same checks as find_badges
+    priority_sum_in(data, alphabet, group_size).map(|(priority_sum, _badges)| priority_sum)
+}
@@ -0,0 +1,134 @@
+use super::{Rucksack, get_rucksacks};
+use super::alphabet::ItemAlphabet;
+
+// Plans for repacking rucksacks so that no item type is split across both compartments
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compartment {
+    First,
+    Second
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Swap { // Exchange two items between the compartments of one rucksack, indexes are within each compartment
+    pub first_index: usize,
+    pub second_index: usize,
+    pub first_item: char,
+    pub second_item: char
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ItemMove { // Take one item out of a rucksack and put it in another, rucksacks are indexes within the group
+    pub item: char,
+    pub from: (usize, Compartment),
+    pub to: (usize, Compartment)
+}
+
+type ItemCounts = Vec<usize>; // Indexed by position in the alphabet
+
+fn count_items(items: &str, alphabet: &ItemAlphabet) -> Result<ItemCounts, &'static str> { // Rucksacks can be parsed with any alphabet, so check again
+    let mut counts = vec![0; alphabet.len()];
+    for item in items.chars() {
+        counts[alphabet.index_of(item).ok_or("Item isn't in the alphabet")?] += 1;
+    }
+    Ok(counts)
+}
+
+// Swaps keep both compartments the same size, so the first compartment has to end up holding a set of whole item types
+// adding up to exactly its size. Each item of a type that ends up in the first compartment but started in the second costs
+// one swap, so pick the types with a knapsack style dp that minimises how many items come across
+pub fn plan_swaps(rucksack: &Rucksack, alphabet: &ItemAlphabet) -> Result<Vec<Swap>, &'static str> {
+    let first = count_items(&rucksack.first_component, alphabet)?;
+    let second = count_items(&rucksack.second_component, alphabet)?;
+    let size = rucksack.first_component.chars().count();
+    let item_types = (0..alphabet.len()).filter(|&p| first[p] + second[p] > 0).collect::<Vec<usize>>();
+
+    // best[i][s] = fewest items moved into the first compartment using types from item_types[..i] that fill s slots
+    let mut best = vec![vec![None; size + 1]; item_types.len() + 1];
+    best[0][0] = Some(0);
+    for (i, &p) in item_types.iter().enumerate() {
+        let total = first[p] + second[p];
+        for s in 0 ..= size {
+            let skip = best[i][s]; // Type goes to the second compartment
+            let take = if s >= total { best[i][s - total].map(|cost: usize| cost + second[p]) } else { None };
+            best[i + 1][s] = match (skip, take) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b)
+            };
+        }
+    }
+    if best[item_types.len()][size].is_none() {
+        return Err("Item types can't be split evenly between compartments")
+    }
+
+    let mut keep_first = vec![false; alphabet.len()];
+    let mut s = size;
+    for i in (0..item_types.len()).rev() { // Walk the table back to see which types were taken
+        let p = item_types[i];
+        let total = first[p] + second[p];
+        if best[i + 1][s] != best[i][s] || best[i][s].is_none() {
+            keep_first[p] = true;
+            s -= total;
+        }
+    }
+
+    let kept_first = |c: &char| keep_first[alphabet.index_of(*c).unwrap()]; // Counting already checked every item
+    let leaving_first = rucksack.first_component.chars().enumerate().filter(|(_, c)| !kept_first(c));
+    let leaving_second = rucksack.second_component.chars().enumerate().filter(|(_, c)| kept_first(c));
+
+    Ok(leaving_first
+        .zip(leaving_second)
+        .map(|((first_index, first_item), (second_index, second_item))| Swap { first_index, second_index, first_item, second_item })
+        .collect())
+}
+
+// Moving items to other rucksacks doesn't have to keep compartments balanced, so every split item type just needs its smaller
+// side moved out, which is also the least possible. Items go to a rucksack that already keeps that type, so they never make a new duplicate
+pub fn plan_group_moves(group: &[Rucksack], alphabet: &ItemAlphabet) -> Result<Vec<ItemMove>, &'static str> {
+    if group.len() < 2 {
+        return Err("Need at least two rucksacks to move items between")
+    }
+
+    let counts = group
+        .iter()
+        .map(|rucksack| Ok((count_items(&rucksack.first_component, alphabet)?, count_items(&rucksack.second_component, alphabet)?)))
+        .collect::<Result<Vec<(ItemCounts, ItemCounts)>, &'static str>>()?;
+
+    let kept_side = |rucksack: usize, p: usize| -> Option<Compartment> { // Where this rucksack will hold the type once fixed
+        let (first, second) = &counts[rucksack];
+        match (first[p], second[p]) {
+            (0, 0) => None,
+            (a, b) if a >= b => Some(Compartment::First),
+            _ => Some(Compartment::Second)
+        }
+    };
+
+    let mut moves = Vec::<ItemMove>::new();
+    for (from, (first, second)) in counts.iter().enumerate() {
+        for p in 0..alphabet.len() {
+            if first[p] == 0 || second[p] == 0 {
+                continue
+            }
+            let (leaving, amount) = if first[p] >= second[p] { (Compartment::Second, second[p]) } else { (Compartment::First, first[p]) };
+            let to = (0..group.len())
+                .filter(|&other| other != from)
+                .find_map(|other| kept_side(other, p).map(|side| (other, side)))
+                .unwrap_or(((from + 1) % group.len(), Compartment::First)); // Nobody else has any, anywhere will do
+
+            for _ in 0..amount {
+                moves.push(ItemMove { item: alphabet.item_at(p).unwrap(), from: (from, leaving), to });
+            }
+        }
+    }
+    Ok(moves)
+}
+
+pub fn get_swap_plans(data: String) -> Vec<Result<Vec<Swap>, &'static str>> { // One plan per rucksack
+    let alphabet = ItemAlphabet::standard();
+    get_rucksacks(data).iter().map(|rucksack| plan_swaps(rucksack, &alphabet)).collect()
+}
+
+pub fn get_group_move_plans(data: String, group_size: usize) -> Vec<Result<Vec<ItemMove>, &'static str>> { // One plan per group
+    let alphabet = ItemAlphabet::standard();
+    get_rucksacks(data).chunks(group_size.max(1)).map(|group| plan_group_moves(group, &alphabet)).collect()
+}
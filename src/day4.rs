@@ -1,59 +1,62 @@
 use std::fs;
 
+use coverage::CoverageReport;
+use crew::{CrewSelection, select_crew};
+use group::{GroupReport, parse_group};
+use interval::Interval;
+use tree::IntervalTree;
+
+pub mod coverage;
+pub mod crew;
+pub mod group;
+pub mod interval;
+pub mod tree;
 
 const PUZZLE_INPUT: &str = "data/pairs.txt";
 
-struct Assignment {
-    start: u32,
-    finish: u32
+pub type Assignment = Interval<u32>;
+
+fn get_assignment_groups(data: String) -> Vec<Vec<Assignment>> {
+    data.split('\n').map(|line| 
+        parse_group(line).unwrap() // * todo error check parsing
+    ).collect::<Vec<Vec<Assignment>>>()
 }
 
-impl Assignment {
-    fn contains(&self, other: &Assignment) -> bool { 
-        self.start <= other.start && self.finish >= other.finish
-    }
-    fn overlapping(&self, other: &Assignment) -> bool { 
-        (self.start >= other.start && self.start <= other.finish || self.finish >= other.start && self.finish <= other.finish) // first assignment overlaps
-        ||
-        (other.start >= self.start && other.start <= self.finish || other.finish >= self.start && other.finish <= self.finish) // second assignment overlaps
-    }
+pub fn get_group_reports() -> Vec<GroupReport> {
+    let input = fs::read_to_string(PUZZLE_INPUT).expect("Can't read file");
+    get_assignment_groups(input).into_iter().map(GroupReport::new).collect()
 }
 
-impl TryFrom<&str> for Assignment {
-    type Error = &'static str;
+fn get_assignments(data: String) -> Vec<Assignment> { // Every elf on its own, elf n is the nth range in the file
+    get_assignment_groups(data).into_iter().flatten().collect()
+}
 
-    fn try_from(range: &str) -> Result<Self, Self::Error> {
-        match range.split_once("-") {
-            Some((start, finish)) => Ok(Assignment{start: start.parse::<u32>().unwrap(), finish: finish.parse::<u32>().unwrap()}), // * todo handle error in get_assignment_pairs
-            None => Err("Invalid format"),
-        }
-    }
+pub fn get_coverage_report() -> CoverageReport {
+    let input = fs::read_to_string(PUZZLE_INPUT).expect("Can't read file");
+    CoverageReport::analyse(&get_assignments(input), None)
 }
 
-fn get_assignment_pairs(data: String) -> Vec<(Assignment, Assignment)> {
-    data.split('\n').map(|line| {
-        let (first, second) = line.split_once(",").unwrap(); 
-        (Assignment::try_from(first).unwrap(), Assignment::try_from(second).unwrap()) // * todo error check parsing
-    }).collect::<Vec<(Assignment, Assignment)>>()
+pub fn get_minimum_crew(target: &Interval<u32>) -> Result<CrewSelection, Vec<Interval<u32>>> {
+    let input = fs::read_to_string(PUZZLE_INPUT).expect("Can't read file");
+    select_crew(&get_assignments(input), target)
 }
 
-pub fn get_contains_pair_count() -> u32 {
+pub fn get_all_overlapping_pair_count() -> usize { // Across every elf, not just within the listed pairs
     let input = fs::read_to_string(PUZZLE_INPUT).expect("Can't read file");
-    let assignment_pairs = get_assignment_pairs(input);
+    IntervalTree::new(&get_assignments(input)).count_overlapping_pairs()
+}
 
-    let total_containing = assignment_pairs.iter().filter(|pair|
-        pair.0.contains(&pair.1) || pair.1.contains(&pair.0)
+pub fn get_contains_pair_count() -> u32 { // Pairs are just groups of two
+    let total_containing = get_group_reports().iter().filter(|group|
+        group.any_contained()
     ).count();
     
     total_containing as u32
 }
 
 pub fn get_overlapping_pair_count() -> u32 {
-    let input = fs::read_to_string(PUZZLE_INPUT).expect("Can't read file");
-    let assignment_pairs = get_assignment_pairs(input);
-
-    let total_overlapping = assignment_pairs.iter().filter(|pair|
-        pair.0.overlapping(&pair.1)
+    let total_overlapping = get_group_reports().iter().filter(|group|
+        group.any_overlapping()
     ).count();
  
     total_overlapping as u32
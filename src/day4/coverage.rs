@@ -0,0 +1,128 @@
+use std::fmt;
+
+use super::Assignment;
+use super::interval::Interval;
+
+// Whole camp analysis. Sweeps over assignment start/finish events to split the sections into runs
+// where the same number of elves are working, so the cost doesn't depend on how wide the ranges are
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CoverageRun {
+    pub sections: Interval<u32>,
+    pub elves: usize
+}
+
+#[derive(Clone, Debug)]
+pub struct CoverageReport {
+    pub bounds: Interval<u32>,
+    pub runs: Vec<CoverageRun>,       // Sorted, back to back across the whole of bounds, including 0 elf runs
+    pub uncovered: Vec<Interval<u32>>,
+    pub max_elves: usize,
+    pub busiest: Vec<Interval<u32>>,   // Where max_elves happens
+    pub redundant_elves: Vec<usize>    // Each one is covered by others on its own, but removing two might leave a gap
+}
+
+fn sweep(assignments: &[Assignment], bounds: &Interval<u32>) -> Vec<CoverageRun> {
+    let mut events = Vec::<(u64, i64)>::with_capacity(assignments.len() * 2 + 2); // (section, change in elves), u64 so finish + 1 can't overflow
+    for assignment in assignments {
+        events.push((assignment.start as u64, 1));
+        events.push((assignment.finish as u64 + 1, -1));
+    }
+    events.push((bounds.start as u64, 0)); // Make sure runs start and end on the bounds
+    events.push((bounds.finish as u64 + 1, 0));
+    events.sort();
+
+    let mut runs = Vec::<CoverageRun>::new();
+    let mut elves = 0i64;
+    let mut i = 0;
+    while i < events.len() {
+        let section = events[i].0;
+        while i < events.len() && events[i].0 == section { // Apply every event at this section before starting a run
+            elves += events[i].1;
+            i += 1;
+        }
+        if let Some(&(next_section, _)) = events.get(i) { // Clip the run to the bounds, dropping it if it's outside them
+            let start = section.max(bounds.start as u64);
+            let finish = (next_section - 1).min(bounds.finish as u64);
+            if start <= finish {
+                runs.push(CoverageRun { sections: Interval { start: start as u32, finish: finish as u32 }, elves: elves as usize });
+            }
+        }
+    }
+    runs
+}
+
+impl CoverageReport {
+    pub fn analyse(assignments: &[Assignment], bounds: Option<Interval<u32>>) -> CoverageReport { // Bounds default to first to last section worked
+        let bounds = bounds.unwrap_or_else(|| Interval {
+            start: assignments.iter().map(|a| a.start).min().unwrap_or(0),
+            finish: assignments.iter().map(|a| a.finish).max().unwrap_or(0)
+        });
+        let runs = sweep(assignments, &bounds);
+
+        let uncovered = runs.iter().filter(|run| run.elves == 0).map(|run| run.sections).collect::<Vec<Interval<u32>>>();
+        let max_elves = runs.iter().map(|run| run.elves).max().unwrap_or(0);
+        let busiest = runs.iter().filter(|run| run.elves == max_elves && max_elves > 0).map(|run| run.sections).collect();
+
+        let redundant_elves = assignments
+            .iter()
+            .enumerate()
+            .filter(|(_, assignment)| { // Every run it's part of needs someone else too
+                let first = runs.partition_point(|run| run.sections.finish < assignment.start);
+                runs[first..]
+                    .iter()
+                    .take_while(|run| run.sections.start <= assignment.finish)
+                    .all(|run| run.elves >= 2)
+            })
+            .map(|(elf, _)| elf)
+            .collect();
+
+        CoverageReport { bounds, runs, uncovered, max_elves, busiest, redundant_elves }
+    }
+
+    pub fn elves_at(&self, section: u32) -> usize {
+        let i = self.runs.partition_point(|run| run.sections.finish < section);
+        self.runs.get(i).filter(|run| run.sections.contains_point(section)).map_or(0, |run| run.elves)
+    }
+
+    pub fn section_counts(&self) -> Vec<(u32, usize)> { // One entry per section in bounds
+        self.runs
+            .iter()
+            .flat_map(|run| (run.sections.start ..= run.sections.finish).map(move |section| (section, run.elves)))
+            .collect()
+    }
+
+    pub fn strip(&self) -> String { // One char per section, ' ' nobody, then the elf count if it fits in a digit, else shaded relative to the busiest section
+        const SHADES: [char; 9] = ['.', ':', '-', '=', '+', '*', '#', '%', '@'];
+        self.section_counts()
+            .iter()
+            .map(|(_, elves)| match elves {
+                0 => ' ',
+                _ if self.max_elves <= 9 => char::from_digit(*elves as u32, 10).unwrap(),
+                _ => SHADES[(elves * SHADES.len() - 1) / self.max_elves]
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for CoverageReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Sections {}-{}", self.bounds.start, self.bounds.finish)?;
+        writeln!(f, "{:>7}  {:>7}  {:>5}", "From", "To", "Elves")?;
+        for run in &self.runs {
+            writeln!(f, "{:>7}  {:>7}  {:>5}", run.sections.start, run.sections.finish, run.elves)?;
+        }
+        writeln!(f)?;
+
+        let ranges = |intervals: &[Interval<u32>]| intervals
+            .iter()
+            .map(|i| format!("{}-{}", i.start, i.finish))
+            .collect::<Vec<String>>()
+            .join(", ");
+        writeln!(f, "Uncovered: {}", if self.uncovered.is_empty() { "none".to_string() } else { ranges(&self.uncovered) })?;
+        writeln!(f, "Most elves on one section: {} at {}", self.max_elves, ranges(&self.busiest))?;
+        writeln!(f, "Redundant elves: {}", self.redundant_elves.len())?;
+        writeln!(f)?;
+        write!(f, "|{}|", self.strip())
+    }
+}
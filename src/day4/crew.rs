@@ -0,0 +1,41 @@
+use super::Assignment;
+use super::interval::{Interval, IntervalSet};
+
+// Smallest set of elves that covers a target range, using the greedy interval cover: from the first
+// section not covered yet, take whichever elf that starts by then reaches furthest
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CrewSelection {
+    pub chosen: Vec<usize>,   // Elf numbers, in the order they were picked (so by start section)
+    pub sent_home: Vec<usize>
+}
+
+pub fn select_crew(assignments: &[Assignment], target: &Interval<u32>) -> Result<CrewSelection, Vec<Interval<u32>>> { // Err holds the sections nobody works
+    let gaps = assignments.iter().copied().collect::<IntervalSet<u32>>().gaps_within(target);
+    if !gaps.is_empty() {
+        return Err(gaps)
+    }
+
+    let mut by_start = (0..assignments.len()).collect::<Vec<usize>>();
+    by_start.sort_by_key(|&elf| assignments[elf].start);
+
+    let mut chosen = Vec::<usize>::new();
+    let mut next_uncovered = target.start as u64; // u64 so it can go past u32::MAX at the end
+    let mut i = 0;
+    while next_uncovered <= target.finish as u64 {
+        let mut best: Option<usize> = None;
+        while i < by_start.len() && assignments[by_start[i]].start as u64 <= next_uncovered {
+            let elf = by_start[i];
+            if best.is_none_or(|b| assignments[elf].finish > assignments[b].finish) {
+                best = Some(elf);
+            }
+            i += 1;
+        }
+        let elf = best.expect("No gaps so someone always reaches the next section");
+        chosen.push(elf);
+        next_uncovered = assignments[elf].finish as u64 + 1;
+    }
+
+    let sent_home = (0..assignments.len()).filter(|elf| !chosen.contains(elf)).collect();
+    Ok(CrewSelection { chosen, sent_home })
+}
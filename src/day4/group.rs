@@ -0,0 +1,48 @@
+use super::Assignment;
+use super::interval::{Interval, IntervalSet};
+
+// Lines with any number of ranges, e.g. "2-4,6-8,3-7" is a group of three elves
+
+#[derive(Clone, Debug)]
+pub struct GroupReport {
+    pub members: Vec<Assignment>,
+    pub contained_members: usize,          // Members whose whole range another member also covers
+    pub overlaps: Vec<Vec<bool>>,          // overlaps[i][j], false on the diagonal
+    pub union: IntervalSet<u32>,
+    pub intersection: Option<Interval<u32>> // Sections every member works, None if there aren't any
+}
+
+impl GroupReport {
+    pub fn new(members: Vec<Assignment>) -> GroupReport {
+        let overlaps = (0..members.len())
+            .map(|i| (0..members.len()).map(|j| i != j && members[i].overlaps(&members[j])).collect())
+            .collect::<Vec<Vec<bool>>>();
+
+        let contained_members = (0..members.len())
+            .filter(|&i| (0..members.len()).any(|j| i != j && members[j].contains(&members[i])))
+            .count();
+
+        let union = members.iter().copied().collect::<IntervalSet<u32>>();
+        let intersection = members
+            .split_first()
+            .and_then(|(first, rest)| rest.iter().try_fold(*first, |common, member| common.intersection(member)));
+
+        GroupReport { members, contained_members, overlaps, union, intersection }
+    }
+
+    pub fn any_contained(&self) -> bool {
+        self.contained_members > 0
+    }
+
+    pub fn any_overlapping(&self) -> bool {
+        self.overlaps.iter().flatten().any(|&overlap| overlap)
+    }
+
+    pub fn overlapping_pair_count(&self) -> usize { // Each pair shows up twice in the matrix
+        self.overlaps.iter().flatten().filter(|&&overlap| overlap).count() / 2
+    }
+}
+
+pub fn parse_group(line: &str) -> Result<Vec<Assignment>, &'static str> {
+    line.split(',').map(Assignment::try_from).collect()
+}
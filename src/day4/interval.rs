@@ -0,0 +1,242 @@
+use std::{fmt::Debug, str::FromStr};
+
+// Inclusive ranges over whole numbers, sections 2-4 means sections 2, 3 and 4
+
+pub trait Discrete: Copy + Ord + Debug {
+    fn next(self) -> Option<Self>; // None at the ends of the type's range
+    fn prev(self) -> Option<Self>;
+    fn distance(from: Self, to: Self) -> u64; // Number of steps from `from` up to `to`
+}
+
+macro_rules! impl_discrete {
+    ($($t:ty),*) => {
+        $(impl Discrete for $t {
+            fn next(self) -> Option<Self> {
+                self.checked_add(1)
+            }
+
+            fn prev(self) -> Option<Self> {
+                self.checked_sub(1)
+            }
+
+            fn distance(from: Self, to: Self) -> u64 {
+                (to as i128 - from as i128) as u64
+            }
+        })*
+    };
+}
+
+impl_discrete!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval<T> {
+    pub start: T,
+    pub finish: T
+}
+
+impl<T: Discrete> Interval<T> {
+    pub fn new(start: T, finish: T) -> Option<Interval<T>> { // None for backwards ranges
+        if start <= finish {
+            Some(Interval { start, finish })
+        }
+        else {
+            None
+        }
+    }
+
+    pub fn point(section: T) -> Interval<T> {
+        Interval { start: section, finish: section }
+    }
+
+    pub fn len(&self) -> u64 { // Number of sections covered, never 0
+        T::distance(self.start, self.finish) + 1
+    }
+
+    pub fn contains(&self, other: &Interval<T>) -> bool {
+        self.start <= other.start && self.finish >= other.finish
+    }
+
+    pub fn contains_point(&self, section: T) -> bool {
+        self.start <= section && section <= self.finish
+    }
+
+    pub fn overlaps(&self, other: &Interval<T>) -> bool { // Neither one finishes before the other starts
+        self.start <= other.finish && other.start <= self.finish
+    }
+
+    pub fn touches(&self, other: &Interval<T>) -> bool { // Overlapping or directly next to each other, so a union is one interval
+        self.overlaps(other) || self.finish.next() == Some(other.start) || other.finish.next() == Some(self.start)
+    }
+
+    pub fn intersection(&self, other: &Interval<T>) -> Option<Interval<T>> {
+        Interval::new(self.start.max(other.start), self.finish.min(other.finish))
+    }
+
+    pub fn union(&self, other: &Interval<T>) -> Option<Interval<T>> { // None if there'd be a gap, use IntervalSet for those
+        if self.touches(other) {
+            Some(Interval { start: self.start.min(other.start), finish: self.finish.max(other.finish) })
+        }
+        else {
+            None
+        }
+    }
+
+    pub fn difference(&self, other: &Interval<T>) -> Vec<Interval<T>> { // Parts of self not in other, 0 to 2 intervals
+        if !self.overlaps(other) {
+            return vec![*self]
+        }
+        let mut parts = Vec::<Interval<T>>::with_capacity(2);
+        if self.start < other.start {
+            parts.push(Interval { start: self.start, finish: other.start.prev().unwrap() });
+        }
+        if other.finish < self.finish {
+            parts.push(Interval { start: other.finish.next().unwrap(), finish: self.finish });
+        }
+        parts
+    }
+}
+
+impl<T: Discrete + FromStr> TryFrom<&str> for Interval<T> {
+    type Error = &'static str;
+
+    fn try_from(range: &str) -> Result<Self, Self::Error> { // "2-4"
+        let (start, finish) = range.split_once('-').ok_or("Invalid format")?;
+        let start = start.parse::<T>().map_err(|_| "Invalid section")?;
+        let finish = finish.parse::<T>().map_err(|_| "Invalid section")?;
+        Interval::new(start, finish).ok_or("Range finishes before it starts")
+    }
+}
+
+// Disjoint intervals kept sorted, touching intervals are merged so each run of sections is stored once
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>
+}
+
+impl<T: Discrete> IntervalSet<T> {
+    pub fn new() -> IntervalSet<T> {
+        IntervalSet { intervals: Vec::new() }
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>) {
+        // Everything in lo .. hi touches the new interval and gets merged into it
+        let lo = self.intervals.partition_point(|existing| existing.finish < interval.start && !existing.touches(&interval));
+        let hi = self.intervals.partition_point(|existing| existing.start <= interval.finish || existing.touches(&interval));
+        let merged = self.intervals[lo..hi].iter().fold(interval, |merged, existing| merged.union(existing).unwrap());
+        self.intervals.splice(lo..hi, [merged]);
+    }
+
+    pub fn remove(&mut self, interval: Interval<T>) {
+        let lo = self.intervals.partition_point(|existing| existing.finish < interval.start);
+        let hi = self.intervals.partition_point(|existing| existing.start <= interval.finish);
+        let remaining = self.intervals[lo..hi]
+            .iter()
+            .flat_map(|existing| existing.difference(&interval))
+            .collect::<Vec<Interval<T>>>();
+        self.intervals.splice(lo..hi, remaining);
+    }
+
+    pub fn contains_point(&self, section: T) -> bool {
+        let i = self.intervals.partition_point(|existing| existing.finish < section);
+        self.intervals.get(i).is_some_and(|existing| existing.contains_point(section))
+    }
+
+    pub fn contains(&self, interval: &Interval<T>) -> bool { // Whole interval is covered, merging means it has to be by one stored interval
+        let i = self.intervals.partition_point(|existing| existing.finish < interval.start);
+        self.intervals.get(i).is_some_and(|existing| existing.contains(interval))
+    }
+
+    pub fn overlapping(&self, interval: &Interval<T>) -> &[Interval<T>] { // Stored intervals sharing any section with the query
+        let lo = self.intervals.partition_point(|existing| existing.finish < interval.start);
+        let hi = self.intervals.partition_point(|existing| existing.start <= interval.finish);
+        &self.intervals[lo..hi]
+    }
+
+    pub fn gaps_within(&self, bounds: &Interval<T>) -> Vec<Interval<T>> { // Parts of bounds not covered by the set
+        self.overlapping(bounds)
+            .iter()
+            .fold(vec![*bounds], |remaining, existing| {
+                remaining.iter().flat_map(|part| part.difference(existing)).collect()
+            })
+    }
+
+    pub fn len(&self) -> u64 { // Total sections covered
+        self.intervals.iter().map(|interval| interval.len()).sum::<u64>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Interval<T>> {
+        self.intervals.iter()
+    }
+}
+
+impl<T: Discrete> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        let mut set = IntervalSet::new();
+        iter.into_iter().for_each(|interval| set.insert(interval));
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::rng::Rng;
+
+    fn random_interval(rng: &mut Rng) -> Interval<u8> {
+        let start = rng.below(40) as u8;
+        Interval { start, finish: start + rng.below(8) as u8 }
+    }
+
+    fn check_against(set: &IntervalSet<u8>, sections: &BTreeSet<u8>) {
+        let stored = set.iter().flat_map(|interval| interval.start ..= interval.finish).collect::<Vec<u8>>();
+        assert_eq!(stored, sections.iter().copied().collect::<Vec<u8>>());
+        assert!(set.intervals.windows(2).all(|pair| pair[0].finish.next() < Some(pair[1].start)), "Touching intervals weren't merged: {:?}", set);
+        assert_eq!(set.len(), sections.len() as u64);
+        for section in 0..50 {
+            assert_eq!(set.contains_point(section), sections.contains(&section));
+        }
+    }
+
+    #[test]
+    fn insert_and_remove_match_a_set_of_sections() {
+        let mut rng = Rng::new(34);
+        for _ in 0..200 {
+            let (mut set, mut sections) = (IntervalSet::<u8>::new(), BTreeSet::<u8>::new());
+            for _ in 0..20 {
+                let interval = random_interval(&mut rng);
+                if rng.below(3) == 0 {
+                    set.remove(interval);
+                    (interval.start ..= interval.finish).for_each(|section| { sections.remove(&section); });
+                }
+                else {
+                    set.insert(interval);
+                    sections.extend(interval.start ..= interval.finish);
+                }
+                check_against(&set, &sections);
+            }
+        }
+    }
+
+    #[test]
+    fn touching_intervals_merge() {
+        let set = [Interval { start: 1, finish: 3 }, Interval { start: 4, finish: 6 }, Interval { start: 8, finish: 9 }].into_iter().collect::<IntervalSet<u32>>();
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), vec![Interval { start: 1, finish: 6 }, Interval { start: 8, finish: 9 }]);
+        assert_eq!(set.gaps_within(&Interval { start: 0, finish: 10 }), vec![Interval::point(0), Interval::point(7), Interval::point(10)]);
+    }
+
+    #[test]
+    fn type_bounds_dont_overflow() {
+        let mut set = IntervalSet::<u8>::new();
+        set.insert(Interval { start: 250, finish: u8::MAX });
+        set.insert(Interval { start: 0, finish: 5 });
+        set.remove(Interval { start: u8::MAX, finish: u8::MAX });
+        set.remove(Interval::point(0));
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), vec![Interval { start: 1, finish: 5 }, Interval { start: 250, finish: 254 }]);
+    }
+}
@@ -0,0 +1,136 @@
+use super::interval::{Discrete, Interval};
+
+// Static interval tree. Intervals are sorted by start and the tree is implicit over that array, the node for a
+// range of indexes being its middle element. Each node stores the furthest finish in its subtree so whole branches
+// can be skipped, which makes queries O(log n + matches)
+
+pub struct IntervalTree<T> {
+    intervals: Vec<(Interval<T>, usize)>, // (interval, position in the input), sorted by start
+    max_finish: Vec<T>                    // Indexed like intervals, furthest finish in the subtree rooted there
+}
+
+impl<T: Discrete> IntervalTree<T> {
+    pub fn new(intervals: &[Interval<T>]) -> IntervalTree<T> {
+        let mut sorted = intervals.iter().copied().zip(0..).collect::<Vec<(Interval<T>, usize)>>();
+        sorted.sort();
+        let mut max_finish = sorted.iter().map(|(interval, _)| interval.finish).collect::<Vec<T>>();
+        build(&sorted, &mut max_finish, 0, sorted.len());
+        IntervalTree { intervals: sorted, max_finish }
+    }
+
+    pub fn len(&self) -> usize {
+        self.intervals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn overlapping(&self, query: &Interval<T>) -> Vec<usize> { // Input positions of every interval sharing a section with query
+        let mut found = Vec::<usize>::new();
+        self.search(query, 0, self.intervals.len(), &mut found);
+        found.sort();
+        found
+    }
+
+    pub fn containing(&self, section: T) -> Vec<usize> {
+        self.overlapping(&Interval::point(section))
+    }
+
+    fn search(&self, query: &Interval<T>, lo: usize, hi: usize, found: &mut Vec<usize>) {
+        if lo >= hi {
+            return
+        }
+        let mid = lo + (hi - lo) / 2;
+        if self.max_finish[mid] < query.start { // Everything under here finishes too early
+            return
+        }
+        self.search(query, lo, mid, found);
+        let (interval, position) = self.intervals[mid];
+        if interval.start <= query.finish { // Otherwise the right side starts too late as well
+            if interval.overlaps(query) {
+                found.push(position);
+            }
+            self.search(query, mid + 1, hi, found);
+        }
+    }
+
+    // Pairs (i, j) overlap when the later starting one starts before the other finishes. Going through in start order,
+    // the earlier intervals that don't overlap interval i are exactly those finishing before it starts, which a binary
+    // search over the sorted finishes counts, so this is O(n log n)
+    pub fn count_overlapping_pairs(&self) -> usize {
+        let mut finishes = self.intervals.iter().map(|(interval, _)| interval.finish).collect::<Vec<T>>();
+        finishes.sort();
+        self.intervals
+            .iter()
+            .enumerate()
+            .map(|(i, (interval, _))| i - finishes.partition_point(|finish| *finish < interval.start))
+            .sum::<usize>()
+    }
+}
+
+fn build<T: Discrete>(sorted: &[(Interval<T>, usize)], max_finish: &mut [T], lo: usize, hi: usize) -> Option<T> { // Returns the subtree max
+    if lo >= hi {
+        return None
+    }
+    let mid = lo + (hi - lo) / 2;
+    let left = build(sorted, max_finish, lo, mid);
+    let right = build(sorted, max_finish, mid + 1, hi);
+    let max = [left, right].into_iter().flatten().fold(sorted[mid].0.finish, |max, finish| max.max(finish));
+    max_finish[mid] = max;
+    Some(max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn random_intervals(rng: &mut Rng, count: usize) -> Vec<Interval<u32>> {
+        (0..count)
+            .map(|_| {
+                let start = rng.below(60) as u32;
+                Interval { start, finish: start + rng.below(10) as u32 }
+            })
+            .collect()
+    }
+
+    fn brute_force_pairs(intervals: &[Interval<u32>]) -> usize {
+        (0..intervals.len())
+            .flat_map(|i| (i + 1 .. intervals.len()).map(move |j| (i, j)))
+            .filter(|&(i, j)| intervals[i].overlaps(&intervals[j]))
+            .count()
+    }
+
+    #[test]
+    fn overlapping_pairs_match_brute_force() {
+        let mut rng = Rng::new(37);
+        for count in 0..60 {
+            let intervals = random_intervals(&mut rng, count);
+            assert_eq!(IntervalTree::new(&intervals).count_overlapping_pairs(), brute_force_pairs(&intervals), "{:?}", intervals);
+        }
+    }
+
+    #[test]
+    fn queries_match_brute_force() {
+        let mut rng = Rng::new(2022);
+        for count in 0..40 {
+            let intervals = random_intervals(&mut rng, count);
+            let tree = IntervalTree::new(&intervals);
+            for query in random_intervals(&mut rng, 20) {
+                let expected = (0..intervals.len()).filter(|&i| intervals[i].overlaps(&query)).collect::<Vec<usize>>();
+                assert_eq!(tree.overlapping(&query), expected);
+            }
+            for section in 0..72 {
+                let expected = (0..intervals.len()).filter(|&i| intervals[i].contains_point(section)).collect::<Vec<usize>>();
+                assert_eq!(tree.containing(section), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn identical_intervals_all_overlap() {
+        let intervals = vec![Interval { start: 5, finish: 5 }; 4];
+        assert_eq!(IntervalTree::new(&intervals).count_overlapping_pairs(), 6);
+    }
+}
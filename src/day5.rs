@@ -1,112 +1,266 @@
-use std::{fs, collections::VecDeque};
+use std::{fs, fmt, collections::VecDeque, str::FromStr};
+
+use crane::{CrateMover, CrateMover9000, CrateMover9001, crane_from_name};
+use rope::RopeStacks;
+use crate::{bench::timed, rng::Rng};
+
+pub mod crane;
+pub mod optimiser;
+pub mod planner;
+pub mod render;
+pub mod reverse;
+pub mod rope;
 
 const PUZZLE_INPUT: &str = "data/crates.txt";
 
-type CrateStack = VecDeque<char>;
-type Instruction = Vec<u32>;
+pub type Crate = String; // Label, which can be more than one char
+pub type CrateStack = VecDeque<Crate>;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DrawingError { // Lines and columns count from 1
+    MissingFooter,
+    BadFooter { line: usize, column: usize }, // Stack numbers have to go 1, 2, 3 ...
+    UnexpectedChar { line: usize, column: usize },
+    UnclosedCrate { line: usize, column: usize },
+    EmptyCrate { line: usize, column: usize },
+    Misaligned { line: usize, column: usize }, // Crate isn't over exactly one stack number, or shares a stack with its neighbour
+    FloatingCrate { line: usize, stack: usize }
+}
 
-fn parse_lines(data: &str) -> Vec<Vec<char>> { // Builds vec [ [' ', 'D', ' '], ['N', 'C', ' '] ] ... etc
-    data.split('\n')
-        .map(|line| {
-            let line_chars = line.chars().collect::<Vec<char>>();
-            line_chars 
-                .chunks(4) // cut into 4 char chunks and condense chunks to single char, with ' ' meaning no crate
-                .map(|chunk|
-                    if chunk[1].is_whitespace() { 
-                        ' '
-                    }
-                    else { 
-                        chunk[1] 
-                    } 
-                )
-                .collect::<Vec<char>>()
-        })
-        .take_while(|vec| !vec[0].is_numeric())
-        .collect()
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Move { // Stacks are numbered from 1, like in the input
+    pub count: usize,
+    pub from: usize,
+    pub to: usize
 }
 
-fn get_crate_stacks(data: &str) -> Vec<CrateStack> { // build vector of stacks(which are VecDequeue<char>). Chars popped from back of vector = removed from top of stack (visually)
-    let parsed_lines = parse_lines(data);
-    let mut stacks = Vec::<CrateStack>::new();
+impl FromStr for Move {
+    type Err = &'static str;
 
-    for _ in 0 .. parsed_lines[0].len() { 
-        stacks.push(CrateStack::new())
+    fn from_str(s: &str) -> Result<Self, Self::Err> { // "move 1 from 3 to 2"
+        match s.split(' ').collect::<Vec<&str>>()[..] {
+            ["move", count, "from", from, "to", to] => {
+                let parse = |n: &str| n.parse::<usize>().map_err(|_| "Invalid number in instruction");
+                Ok(Move { count: parse(count)?, from: parse(from)?, to: parse(to)? })
+            }
+            _ => Err("Invalid instruction")
+        }
     }
+}
 
-    parsed_lines // populate 'stacks' vector with crates, using index in vec to assign to correct stack
-        .iter()
-        .for_each(|line|
-            for index in 0 .. line.len() {
-                if !line[index].is_whitespace() {
-                    stacks[index].push_front(line[index])
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { // Same format parse_instructions reads
+        write!(f, "move {} from {} to {}", self.count, self.from, self.to)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExecutionMode {
+    Strict,  // Stop at the first move that can't be done as written
+    Lenient  // Move whatever crates there are and skip moves involving stacks that don't exist
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoveError {
+    NoSuchStack(usize),
+    NotEnoughCrates { requested: usize, available: usize }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExecutionError {
+    pub instruction: usize, // Counting from 1
+    pub reason: MoveError
+}
+
+
+fn parse_footer(footer: &str, line: usize) -> Result<Vec<(usize, usize)>, DrawingError> { // Column span of each stack number, checking they count up from 1
+    let chars = footer.chars().collect::<Vec<char>>();
+    let mut spans = Vec::<(usize, usize)>::new();
+    let mut column = 0;
+    while column < chars.len() {
+        if chars[column].is_whitespace() {
+            column += 1;
+            continue
+        }
+        let start = column;
+        while column < chars.len() && !chars[column].is_whitespace() {
+            column += 1;
+        }
+        let number = chars[start..column].iter().collect::<String>();
+        if number.parse::<usize>() != Ok(spans.len() + 1) {
+            return Err(DrawingError::BadFooter { line, column: start + 1 })
+        }
+        spans.push((start, column - 1));
+    }
+    if spans.is_empty() {
+        return Err(DrawingError::MissingFooter)
+    }
+    Ok(spans)
+}
+
+fn parse_row(row: &str, line: usize, footer: &[(usize, usize)]) -> Result<Vec<(usize, Crate)>, DrawingError> { // (stack index, label) for each crate in a row
+    let chars = row.chars().collect::<Vec<char>>();
+    let mut crates = Vec::<(usize, Crate)>::new();
+    let mut column = 0;
+    while column < chars.len() {
+        match chars[column] {
+            c if c.is_whitespace() => column += 1,
+            '[' => {
+                let open = column;
+                let close = chars[open..].iter().position(|&c| c == ']').map(|offset| open + offset)
+                    .ok_or(DrawingError::UnclosedCrate { line, column: open + 1 })?;
+                if close == open + 1 {
+                    return Err(DrawingError::EmptyCrate { line, column: open + 1 })
+                }
+                // The crate sits on whichever stack number is underneath it
+                let mut under = footer.iter().enumerate().filter(|(_, (start, finish))| *start <= close && open <= *finish);
+                let stack = match (under.next(), under.next()) {
+                    (Some((stack, _)), None) => stack,
+                    _ => return Err(DrawingError::Misaligned { line, column: open + 1 })
+                };
+                if crates.last().is_some_and(|(previous, _)| *previous == stack) {
+                    return Err(DrawingError::Misaligned { line, column: open + 1 })
                 }
+                crates.push((stack, chars[open + 1 .. close].iter().collect()));
+                column = close + 1;
             }
-        );
-    stacks
+            _ => return Err(DrawingError::UnexpectedChar { line, column: column + 1 })
+        }
+    }
+    Ok(crates)
 }
 
-fn parse_instructions(data: &str) -> Vec<Instruction> { // "move 1 from 3 to 2"     ->      "[1, 3, 2]"
+pub fn parse_stacks(data: &str) -> Result<Vec<CrateStack>, DrawingError> { // Stacks are worked out from the numbered footer, so any count or label width works
+    let drawing = data
+        .split('\n')
+        .take_while(|line| !line.trim().is_empty() && !line.starts_with("move"))
+        .collect::<Vec<&str>>();
+    let (footer, rows) = drawing.split_last().ok_or(DrawingError::MissingFooter)?;
+    let footer = parse_footer(footer, rows.len() + 1)?;
+
+    let mut stacks = vec![CrateStack::new(); footer.len()];
+    for (index, row) in rows.iter().enumerate().rev() { // Bottom row first so crates are pushed in stack order
+        let line = index + 1;
+        let crates = parse_row(row, line, &footer)?;
+        for (stack, label) in crates {
+            if stacks[stack].len() != rows.len() - 1 - index { // Nothing underneath it
+                return Err(DrawingError::FloatingCrate { line, stack: stack + 1 })
+            }
+            stacks[stack].push_back(label);
+        }
+    }
+    Ok(stacks)
+}
+
+pub fn get_crate_stacks(data: &str) -> Vec<CrateStack> { // build vector of stacks(which are VecDequeue<Crate>). Crates popped from back of vector = removed from top of stack (visually)
+    parse_stacks(data).unwrap() // todo error handling
+}
+
+pub fn parse_instructions(data: &str) -> Vec<Move> { // "move 1 from 3 to 2"     ->      "Move { count: 1, from: 3, to: 2 }"
     data.split('\n')
         .filter(|line| line.starts_with('m'))
         .map(|instruction_line| 
-            instruction_line
-                .split(' ')
-                .filter_map(|slice| slice.parse::<u32>().ok())
-                .collect::<Instruction>()
-        ).collect::<Vec<Instruction>>()
+            instruction_line.parse::<Move>().unwrap() // todo error handling
+        ).collect::<Vec<Move>>()
 }
 
-// multiple crate mover 
-fn execute_instructions_cm9001(stacks: &mut [CrateStack], instructions: Vec<Instruction>) { 
-    instructions.iter().for_each(|instruction_data| { 
-        let amount = instruction_data[0] as usize;
-        let from = instruction_data[1] as usize;
-        let to = instruction_data[2] as usize;
-
-        let mut temp_crate_vec = VecDeque::<char>::with_capacity(amount);
-
-        for _ in 0 .. amount { // Push crates to intermediate VecDequeue before pushing them to requested stack
-            if let Some(moved_crate) = stacks[from - 1].pop_back() {
-                temp_crate_vec.push_front(moved_crate);
-            }
+fn check_move(stacks: &[CrateStack], instruction: &Move) -> Result<(), MoveError> {
+    for stack in [instruction.from, instruction.to] {
+        if stack == 0 || stack > stacks.len() {
+            return Err(MoveError::NoSuchStack(stack))
         }
+    }
+    let available = stacks[instruction.from - 1].len();
+    if instruction.count > available {
+        return Err(MoveError::NotEnoughCrates { requested: instruction.count, available })
+    }
+    Ok(())
+}
 
-        for _ in 0 .. amount { // pop crates onto requested stack from front
-            if let Some(moved_crate) = temp_crate_vec.pop_front() {
-                stacks[to - 1].push_back(moved_crate);
-            }
+fn execute_instructions(stacks: &mut [CrateStack], instructions: &[Move], crane: &mut dyn CrateMover, mode: ExecutionMode) -> Result<(), ExecutionError> {
+    for (index, instruction) in instructions.iter().enumerate() {
+        match (check_move(stacks, instruction), mode) {
+            (Err(reason), ExecutionMode::Strict) => return Err(ExecutionError { instruction: index + 1, reason }),
+            (Err(MoveError::NoSuchStack(_)), ExecutionMode::Lenient) => continue,
+            _ => crane.move_crates(stacks, instruction.count, instruction.from - 1, instruction.to - 1) // Cranes ignore missing crates
         }
-    });
+    }
+    Ok(())
 }
 
-fn get_top_crates_str(stacks: &[CrateStack]) -> String {
+pub fn get_top_crates_str(stacks: &[CrateStack]) -> String {
     stacks
         .iter()
         .filter_map(|stack| stack.back())
+        .map(|label| label.as_str())
         .collect()
 }
 
 pub fn solve() -> String {
+    solve_with(&mut CrateMover9001, ExecutionMode::Lenient).unwrap()
+}
+
+pub fn solve_with(crane: &mut dyn CrateMover, mode: ExecutionMode) -> Result<String, ExecutionError> {
     let input = fs::read_to_string(PUZZLE_INPUT).expect("Can't read file");
     let mut crate_stacks = get_crate_stacks(&input);
     let instructions = parse_instructions(&input);
-    // execute_instructions_cm9000(&mut crate_stacks, instructions);
-    execute_instructions_cm9001(&mut crate_stacks, instructions);
-    get_top_crates_str(&crate_stacks)
-}
-
-// single crate mover
-// fn execute_instructions_cm9000(stacks: &mut [CrateStack], instructions: Vec<Instruction>) {
-//     instructions.iter().for_each(|instruction_data| {  // * make instruction struct instead of type alias ?
-//         let amount = instruction_data[0];
-//         let from = instruction_data[1] as usize;
-//         let to = instruction_data[2] as usize;
-
-//         for _ in 0 .. amount {
-//             if let Some(moved_crate) = stacks[from - 1].pop_back() {
-//                 stacks[to - 1].push_back(moved_crate);
-//             }
-//         }
-//     });
-// }
+    execute_instructions(&mut crate_stacks, &instructions, crane, mode)?;
+    Ok(get_top_crates_str(&crate_stacks))
+}
+
+pub fn solve_with_crane(name: &str) -> Result<String, &'static str> { // Pick the crane model at runtime, see crane_from_name
+    let mut crane = crane_from_name(name)?;
+    Ok(solve_with(crane.as_mut(), ExecutionMode::Lenient).unwrap()) // Lenient never fails
+}
+
+pub fn generate_crates(stack_count: usize, crates: usize, moves: usize, seed: u64) -> Result<(Vec<CrateStack>, Vec<Move>), &'static str> { // Labels are just the crate numbers, moves never ask for more than there are
+    if stack_count == 0 {
+        return Err("Need at least one stack")
+    }
+    if moves > 0 && (stack_count < 2 || crates == 0) { // Nothing could ever move
+        return Err("Moves need at least two stacks and one crate")
+    }
+    let mut rng = Rng::new(seed);
+
+    let mut stacks = vec![CrateStack::new(); stack_count];
+    for label in 0..crates {
+        stacks[label % stack_count].push_back(label.to_string());
+    }
+    let mut heights = stacks.iter().map(|stack| stack.len()).collect::<Vec<usize>>();
+    let mut instructions = Vec::<Move>::with_capacity(moves);
+    while instructions.len() < moves {
+        let (from, to) = (rng.below(stack_count), rng.below(stack_count));
+        if from == to || heights[from] == 0 {
+            continue
+        }
+        let count = 1 + rng.below(heights[from]);
+        heights[from] -= count;
+        heights[to] += count;
+        instructions.push(Move { count, from: from + 1, to: to + 1 });
+    }
+    Ok((stacks, instructions))
+}
+
+pub fn benchmark_block_moves(stack_count: usize, crates: usize, moves: usize) -> Result<(), &'static str> { // Compare moving crates through VecDeques against splitting and joining ropes
+    let (stacks, instructions) = generate_crates(stack_count, crates, moves, 2022)?;
+    println!("{} crates on {} stacks, {} moves averaging {} crates", crates, stack_count, moves,
+        instructions.iter().map(|m| m.count).sum::<usize>() / moves.max(1));
+
+    for (name, crane, reversed) in [("9000", &mut CrateMover9000 as &mut dyn CrateMover, true), ("9001", &mut CrateMover9001, false)] {
+        let mut deques = stacks.clone();
+        timed(&format!("{} deques", name), || {
+            execute_instructions(&mut deques, &instructions, crane, ExecutionMode::Lenient).unwrap(); // Lenient never fails
+            get_top_crates_str(&deques)
+        });
+        let mut ropes = timed(&format!("{} build", name), || RopeStacks::from_stacks(&stacks));
+        timed(&format!("{} ropes", name), || {
+            ropes.execute(&instructions, reversed);
+            ropes.tops()
+        });
+
+        if deques != ropes.to_stacks() {
+            return Err("Ropes ended up with different stacks")
+        }
+    }
+    Ok(())
+}
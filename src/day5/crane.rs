@@ -0,0 +1,168 @@
+use std::collections::VecDeque;
+
+use super::{Crate, CrateStack};
+
+// Crane models. Every model moves crates by lifting blocks off the top of one stack and putting them on
+// another, they only differ in how big the blocks are and what order the crates come off in
+
+pub trait CrateMover {
+    fn name(&self) -> String;
+    fn move_crates(&mut self, stacks: &mut [CrateStack], amount: usize, from: usize, to: usize); // Stacks indexed from 0
+    fn boxed_clone(&self) -> Box<dyn CrateMover>; // Lets searches branch off a crane partway through a program
+
+    fn state_key(&self) -> usize { // Anything about the crane's own state that changes what later moves do
+        0
+    }
+
+    // Rewrites the optimiser is allowed to make. Defaults suit cranes that only look at the two stacks in a move
+    fn is_noop(&self, count: usize, from: usize, to: usize) -> bool {
+        count == 0 || from == to // Crates get put straight back where they were
+    }
+
+    fn can_merge(&self, _first: usize, _second: usize) -> bool { // Moving first then second between the same stacks is one move of both
+        false
+    }
+
+    fn undone_by_reverse(&self, _count: usize) -> bool { // Moving count crates there and straight back changes nothing
+        false
+    }
+}
+
+fn lift(stacks: &mut [CrateStack], amount: usize, from: usize, to: usize, reversed: bool) { // Missing crates are ignored
+    let mut lifted = VecDeque::<Crate>::with_capacity(amount);
+    for _ in 0 .. amount {
+        if let Some(moved_crate) = stacks[from].pop_back() {
+            lifted.push_front(moved_crate);
+        }
+    }
+    if reversed { // Crates go down in the order they came off, like moving them one at a time
+        lifted.make_contiguous().reverse();
+    }
+    stacks[to].extend(lifted);
+}
+
+#[derive(Clone)]
+pub struct CrateMover9000; // One crate at a time
+
+impl CrateMover for CrateMover9000 {
+    fn name(&self) -> String {
+        "CrateMover 9000".to_string()
+    }
+
+    fn move_crates(&mut self, stacks: &mut [CrateStack], amount: usize, from: usize, to: usize) {
+        lift(stacks, amount, from, to, true)
+    }
+
+    fn is_noop(&self, count: usize, from: usize, to: usize) -> bool { // Lifting onto the same stack flips the top count crates
+        count == 0 || (from == to && count == 1)
+    }
+
+    fn can_merge(&self, _first: usize, _second: usize) -> bool {
+        true
+    }
+
+    fn undone_by_reverse(&self, _count: usize) -> bool { // Reversed twice
+        true
+    }
+
+    fn boxed_clone(&self) -> Box<dyn CrateMover> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct CrateMover9001; // Whole block in one go
+
+impl CrateMover for CrateMover9001 {
+    fn name(&self) -> String {
+        "CrateMover 9001".to_string()
+    }
+
+    fn move_crates(&mut self, stacks: &mut [CrateStack], amount: usize, from: usize, to: usize) {
+        lift(stacks, amount, from, to, false)
+    }
+
+    fn undone_by_reverse(&self, _count: usize) -> bool {
+        true
+    }
+
+    fn boxed_clone(&self) -> Box<dyn CrateMover> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct CappedCrateMover { // Blocks of up to capacity crates, capacity 1 is a 9000
+    pub capacity: usize
+}
+
+impl CrateMover for CappedCrateMover {
+    fn name(&self) -> String {
+        format!("Capped CrateMover ({} per lift)", self.capacity)
+    }
+
+    fn move_crates(&mut self, stacks: &mut [CrateStack], amount: usize, from: usize, to: usize) {
+        let mut remaining = amount;
+        while remaining > 0 {
+            let block = remaining.min(self.capacity.max(1));
+            lift(stacks, block, from, to, false);
+            remaining -= block;
+        }
+    }
+
+    fn can_merge(&self, first: usize, _second: usize) -> bool { // Same lifts as long as the first move doesn't end on a part block
+        first.is_multiple_of(self.capacity.max(1))
+    }
+
+    fn undone_by_reverse(&self, count: usize) -> bool { // A single block comes straight back, otherwise blocks only reverse cleanly when they're all full
+        count <= self.capacity.max(1) || count.is_multiple_of(self.capacity.max(1))
+    }
+
+    fn boxed_clone(&self) -> Box<dyn CrateMover> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct AlternatingCrateMover { // Whole blocks, but every second lift comes off upside down
+    lifts: usize
+}
+
+impl CrateMover for AlternatingCrateMover {
+    fn name(&self) -> String {
+        "Alternating CrateMover".to_string()
+    }
+
+    fn move_crates(&mut self, stacks: &mut [CrateStack], amount: usize, from: usize, to: usize) {
+        lift(stacks, amount, from, to, self.lifts % 2 == 1);
+        self.lifts += 1;
+    }
+
+    fn state_key(&self) -> usize {
+        self.lifts % 2
+    }
+
+    fn is_noop(&self, _count: usize, _from: usize, _to: usize) -> bool { // Every lift flips which way up the next one is, so nothing can be dropped
+        false
+    }
+
+    fn boxed_clone(&self) -> Box<dyn CrateMover> {
+        Box::new(self.clone())
+    }
+}
+
+pub fn crane_from_name(name: &str) -> Result<Box<dyn CrateMover>, &'static str> { // "9000", "9001", "capped:<n>" or "alternating"
+    match name.split_once(':') {
+        None => match name {
+            "9000" => Ok(Box::new(CrateMover9000)),
+            "9001" => Ok(Box::new(CrateMover9001)),
+            "alternating" => Ok(Box::new(AlternatingCrateMover::default())),
+            _ => Err("Unknown crane model")
+        },
+        Some(("capped", capacity)) => match capacity.parse::<usize>() {
+            Ok(capacity) if capacity > 0 => Ok(Box::new(CappedCrateMover { capacity })),
+            _ => Err("Crane capacity must be a positive number")
+        },
+        Some(_) => Err("Unknown crane model")
+    }
+}
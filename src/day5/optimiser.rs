@@ -0,0 +1,77 @@
+use std::{fs, fmt};
+
+use super::{CrateStack, Move, ExecutionMode, ExecutionError, PUZZLE_INPUT, execute_instructions, get_crate_stacks, parse_instructions};
+use super::crane::CrateMover;
+
+// Peephole pass over a program. Which rewrites are safe depends on the crane, so it asks the crane (see the
+// optimiser hooks on CrateMover). Merges and cancellations assume every move has all the crates it asks for,
+// so the original program has to run in strict mode before anything is changed
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OptimisedProgram {
+    pub moves: Vec<Move>,
+    pub before: usize,
+    pub noops_removed: usize,
+    pub merged: usize,    // Moves folded into the one before them
+    pub cancelled: usize  // Moves dropped because the next one put the crates straight back, counting both
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptimiseError {
+    Invalid(ExecutionError), // Original program doesn't run in strict mode
+    Mismatch                 // Rewritten program ends somewhere else, so the crane allowed a rewrite it shouldn't have
+}
+
+impl OptimisedProgram {
+    pub fn after(&self) -> usize {
+        self.moves.len()
+    }
+}
+
+impl fmt::Display for OptimisedProgram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} moves -> {} moves ({} no-ops removed, {} merged, {} cancelled)",
+            self.before, self.after(), self.noops_removed, self.merged, self.cancelled)
+    }
+}
+
+fn run(stacks: &[CrateStack], program: &[Move], crane: &dyn CrateMover) -> Result<Vec<CrateStack>, ExecutionError> {
+    let mut stacks = stacks.to_vec();
+    execute_instructions(&mut stacks, program, crane.boxed_clone().as_mut(), ExecutionMode::Strict)?;
+    Ok(stacks)
+}
+
+pub fn optimise(stacks: &[CrateStack], program: &[Move], crane: &dyn CrateMover) -> Result<OptimisedProgram, OptimiseError> {
+    let expected = run(stacks, program, crane).map_err(OptimiseError::Invalid)?;
+
+    let mut optimised = OptimisedProgram { moves: Vec::with_capacity(program.len()), before: program.len(), noops_removed: 0, merged: 0, cancelled: 0 };
+    for instruction in program {
+        if crane.is_noop(instruction.count, instruction.from, instruction.to) {
+            optimised.noops_removed += 1;
+            continue
+        }
+        match optimised.moves.last_mut() { // Compare against what's been kept, so dropping a pair can expose another one
+            _ if instruction.from == instruction.to => optimised.moves.push(*instruction), // Cranes that aren't a no-op here are left alone
+            Some(last) if last.from == instruction.from && last.to == instruction.to && crane.can_merge(last.count, instruction.count) => {
+                last.count += instruction.count;
+                optimised.merged += 1;
+            }
+            Some(last) if last.from == instruction.to && last.to == instruction.from && last.count == instruction.count
+                && crane.undone_by_reverse(instruction.count) => {
+                optimised.moves.pop();
+                optimised.cancelled += 2;
+            }
+            _ => optimised.moves.push(*instruction)
+        }
+    }
+
+    if run(stacks, &optimised.moves, crane) != Ok(expected) {
+        return Err(OptimiseError::Mismatch)
+    }
+    Ok(optimised)
+}
+
+pub fn optimise_puzzle_program(crane: &dyn CrateMover) -> Result<OptimisedProgram, OptimiseError> {
+    let input = fs::read_to_string(PUZZLE_INPUT).expect("Can't read file");
+    optimise(&get_crate_stacks(&input), &parse_instructions(&input), crane)
+}
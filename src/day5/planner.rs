@@ -0,0 +1,266 @@
+use std::{cmp::Reverse, collections::{BinaryHeap, HashMap}};
+
+use super::{Crate, CrateStack, Move};
+use super::crane::CrateMover;
+
+// Finds the fewest moves that get from one arrangement to another under a given crane, using A*.
+// Both heuristics count stacks that still have to take part in a move, and every move has exactly one source
+// and one destination, so they never overestimate and the first plan found is the shortest
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Target {
+    Arrangement(Vec<CrateStack>),
+    Tops(Vec<Crate>) // Top label of every stack, in order
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SearchLimits {
+    pub max_depth: usize,  // Longest plan to look for
+    pub max_states: usize  // Give up once this many different arrangements have been seen
+}
+
+impl Default for SearchLimits {
+    fn default() -> SearchLimits {
+        SearchLimits { max_depth: 20, max_states: 200_000 }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlanError {
+    StackCountMismatch,
+    CrateMismatch, // Target doesn't have the same crates, so no amount of moving will get there
+    Unreachable,   // Searched everything within max_depth
+    LimitReached
+}
+
+struct Node {
+    stacks: Vec<CrateStack>,
+    crane: Box<dyn CrateMover>,
+    parent: Option<usize>,
+    via: Option<Move>
+}
+
+fn matching_prefix(current: &CrateStack, target: &CrateStack) -> usize { // Crates from the bottom that are already in place
+    current.iter().zip(target).take_while(|(a, b)| a == b).count()
+}
+
+fn estimate(stacks: &[CrateStack], target: &Target) -> usize {
+    match target {
+        Target::Arrangement(goal) => {
+            let (mut sources, mut destinations) = (0, 0);
+            for (current, wanted) in stacks.iter().zip(goal) {
+                let settled = matching_prefix(current, wanted);
+                if current.len() > settled { // Something on top has to be taken off
+                    sources += 1;
+                }
+                if wanted.len() > settled { // Something has to be put on
+                    destinations += 1;
+                }
+            }
+            sources.max(destinations)
+        }
+        Target::Tops(tops) => {
+            let wrong = stacks.iter().zip(tops).filter(|(stack, top)| stack.back() != Some(*top)).count();
+            wrong.div_ceil(2) // A move can fix the top of its source and its destination
+        }
+    }
+}
+
+fn reached(stacks: &[CrateStack], target: &Target) -> bool {
+    match target {
+        Target::Arrangement(goal) => stacks == goal.as_slice(),
+        Target::Tops(tops) => stacks.iter().zip(tops).all(|(stack, top)| stack.back() == Some(top))
+    }
+}
+
+fn sorted_crates(stacks: &[CrateStack]) -> Vec<&Crate> {
+    let mut crates = stacks.iter().flatten().collect::<Vec<&Crate>>();
+    crates.sort();
+    crates
+}
+
+fn enough_crates(stacks: &[CrateStack], tops: &[Crate]) -> bool { // Every label wanted on top exists, as many times as it's wanted
+    let mut available = sorted_crates(stacks);
+    tops.iter().all(|top| match available.binary_search(&top) {
+        Ok(i) => {
+            available.remove(i);
+            true
+        }
+        Err(_) => false
+    })
+}
+
+pub fn plan_moves(start: &[CrateStack], target: &Target, crane: &dyn CrateMover, limits: SearchLimits) -> Result<Vec<Move>, PlanError> {
+    match target {
+        Target::Arrangement(goal) if goal.len() != start.len() => return Err(PlanError::StackCountMismatch),
+        Target::Arrangement(goal) if sorted_crates(goal) != sorted_crates(start) => return Err(PlanError::CrateMismatch),
+        Target::Tops(tops) if tops.len() != start.len() => return Err(PlanError::StackCountMismatch),
+        Target::Tops(tops) if !enough_crates(start, tops) => return Err(PlanError::CrateMismatch),
+        _ => ()
+    }
+
+    let mut nodes = vec![Node { stacks: start.to_vec(), crane: crane.boxed_clone(), parent: None, via: None }];
+    let mut best_depth = HashMap::<(Vec<CrateStack>, usize), usize>::new();
+    best_depth.insert((start.to_vec(), crane.state_key()), 0);
+    let mut queue = BinaryHeap::new(); // Lowest estimated total first, then deepest to finish off promising plans
+    queue.push((Reverse(estimate(start, target)), 0usize, Reverse(0usize)));
+
+    while let Some((_, depth, Reverse(index))) = queue.pop() {
+        let key = (nodes[index].stacks.clone(), nodes[index].crane.state_key());
+        if best_depth[&key] < depth { // Stale entry, a shorter way here was found later
+            continue
+        }
+        if reached(&nodes[index].stacks, target) {
+            let mut moves = Vec::<Move>::new();
+            let mut current = index;
+            while let (Some(parent), Some(via)) = (nodes[current].parent, nodes[current].via) {
+                moves.push(via);
+                current = parent;
+            }
+            moves.reverse();
+            return Ok(moves)
+        }
+        if depth >= limits.max_depth {
+            continue
+        }
+
+        let stack_count = nodes[index].stacks.len();
+        for from in 0..stack_count {
+            for to in (0..stack_count).filter(|&to| to != from) {
+                for count in 1 ..= nodes[index].stacks[from].len() {
+                    let mut stacks = nodes[index].stacks.clone();
+                    let mut next_crane = nodes[index].crane.boxed_clone();
+                    next_crane.move_crates(&mut stacks, count, from, to);
+
+                    let key = (stacks, next_crane.state_key());
+                    if best_depth.get(&key).is_some_and(|&seen| seen <= depth + 1) {
+                        continue
+                    }
+                    if nodes.len() >= limits.max_states {
+                        return Err(PlanError::LimitReached)
+                    }
+                    best_depth.insert(key.clone(), depth + 1);
+                    let priority = depth + 1 + estimate(&key.0, target);
+                    queue.push((Reverse(priority), depth + 1, Reverse(nodes.len())));
+                    nodes.push(Node {
+                        stacks: key.0,
+                        crane: next_crane,
+                        parent: Some(index),
+                        via: Some(Move { count, from: from + 1, to: to + 1 })
+                    });
+                }
+            }
+        }
+    }
+
+    Err(PlanError::Unreachable)
+}
+
+pub fn format_moves(moves: &[Move]) -> String { // One per line, ready for parse_instructions
+    moves.iter().map(|m| m.to_string()).collect::<Vec<String>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashSet, VecDeque};
+
+    use super::*;
+    use super::super::crane::{CrateMover9000, CrateMover9001, AlternatingCrateMover};
+    use crate::rng::Rng;
+
+    fn random_stacks(rng: &mut Rng, stack_count: usize, crates: usize) -> Vec<CrateStack> {
+        let mut stacks = vec![CrateStack::new(); stack_count];
+        for label in ["A", "B", "C", "D", "A"].iter().take(crates) { // Repeated label so identical crates are covered too
+            stacks[rng.below(stack_count)].push_back(label.to_string());
+        }
+        stacks
+    }
+
+    fn shortest_by_bfs(start: &[CrateStack], target: &Target, crane: &dyn CrateMover) -> Option<usize> { // Every move from every state, no heuristic
+        let mut seen = HashSet::<(Vec<CrateStack>, usize)>::new();
+        let mut queue = VecDeque::from([(start.to_vec(), crane.boxed_clone(), 0)]);
+        seen.insert((start.to_vec(), crane.state_key()));
+        while let Some((stacks, crane, depth)) = queue.pop_front() {
+            if reached(&stacks, target) {
+                return Some(depth)
+            }
+            for from in 0..stacks.len() {
+                for to in (0..stacks.len()).filter(|&to| to != from) {
+                    for count in 1 ..= stacks[from].len() {
+                        let (mut next, mut next_crane) = (stacks.clone(), crane.boxed_clone());
+                        next_crane.move_crates(&mut next, count, from, to);
+                        if seen.insert((next.clone(), next_crane.state_key())) {
+                            queue.push_back((next, next_crane, depth + 1));
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn run(start: &[CrateStack], plan: &[Move], crane: &dyn CrateMover) -> Vec<CrateStack> {
+        let (mut stacks, mut crane) = (start.to_vec(), crane.boxed_clone());
+        plan.iter().for_each(|m| crane.move_crates(&mut stacks, m.count, m.from - 1, m.to - 1));
+        stacks
+    }
+
+    fn check(start: &[CrateStack], target: &Target, crane: &dyn CrateMover) {
+        let plan = plan_moves(start, target, crane, SearchLimits::default());
+        match shortest_by_bfs(start, target, crane) {
+            Some(depth) => {
+                let plan = plan.unwrap_or_else(|err| panic!("{:?} from {:?} to {:?}", err, start, target));
+                assert_eq!(plan.len(), depth, "Plan isn't shortest from {:?} to {:?}", start, target);
+                assert!(reached(&run(start, &plan, crane), target));
+            }
+            None => assert_eq!(plan, Err(PlanError::Unreachable))
+        }
+    }
+
+    #[test]
+    fn arrangement_plans_are_shortest() {
+        let mut rng = Rng::new(43);
+        let cranes: [&dyn CrateMover; 3] = [&CrateMover9000, &CrateMover9001, &AlternatingCrateMover::default()];
+        for _ in 0..40 {
+            let (stack_count, crates) = (2 + rng.below(2), 1 + rng.below(5));
+            let (start, goal) = (random_stacks(&mut rng, stack_count, crates), random_stacks(&mut rng, stack_count, crates));
+            for crane in cranes {
+                check(&start, &Target::Arrangement(goal.clone()), crane);
+            }
+        }
+    }
+
+    #[test]
+    fn tops_plans_are_shortest() {
+        let mut rng = Rng::new(2022);
+        let cranes: [&dyn CrateMover; 2] = [&CrateMover9000, &CrateMover9001];
+        for _ in 0..40 {
+            let start = random_stacks(&mut rng, 3, 5);
+            let shuffled = random_stacks(&mut rng, 3, 5);
+            if shuffled.iter().any(|stack| stack.is_empty()) {
+                continue
+            }
+            let tops = Target::Tops(shuffled.iter().map(|stack| stack[0].clone()).collect());
+            for crane in cranes {
+                check(&start, &tops, crane);
+            }
+        }
+    }
+
+    #[test]
+    fn multi_char_tops_already_in_place() {
+        let start = vec![CrateStack::from(["AB".to_string()]), CrateStack::from(["CD".to_string()])];
+        let tops = Target::Tops(vec!["AB".to_string(), "CD".to_string()]);
+        assert_eq!(estimate(&start, &tops), 0);
+        assert_eq!(plan_moves(&start, &tops, &CrateMover9001, SearchLimits::default()), Ok(vec![]));
+    }
+
+    #[test]
+    fn impossible_targets_are_rejected() {
+        let start = vec![CrateStack::from(["A".to_string()]), CrateStack::new()];
+        let limits = SearchLimits::default();
+        assert_eq!(plan_moves(&start, &Target::Tops(vec!["A".to_string()]), &CrateMover9001, limits), Err(PlanError::StackCountMismatch));
+        assert_eq!(plan_moves(&start, &Target::Tops(vec!["A".to_string(), "A".to_string()]), &CrateMover9001, limits), Err(PlanError::CrateMismatch));
+        assert_eq!(plan_moves(&start, &Target::Arrangement(vec![CrateStack::new(), CrateStack::from(["B".to_string()])]), &CrateMover9001, limits), Err(PlanError::CrateMismatch));
+    }
+}
@@ -0,0 +1,83 @@
+use super::{CrateStack, Move, ExecutionMode, ExecutionError, execute_instructions};
+use super::crane::CrateMover;
+
+// Draws stacks in the same format as the puzzle input, so anything parse_stacks reads in the usual layout comes back
+// out byte for byte. Stacks with wider labels get wider columns, with the crates and the stack number centred in them
+
+fn column_width(stack: &CrateStack, number: usize) -> usize {
+    let widest_label = stack.iter().map(|label| label.chars().count()).max().unwrap_or(1);
+    (widest_label + 2).max(number.to_string().len()).max(3)
+}
+
+pub fn render_stacks(stacks: &[CrateStack]) -> String { // Rows top down then the numbered footer, no trailing newline
+    let height = stacks.iter().map(|stack| stack.len()).max().unwrap_or(0);
+    let widths = stacks.iter().enumerate().map(|(i, stack)| column_width(stack, i + 1)).collect::<Vec<usize>>();
+
+    let mut lines = (0..height)
+        .rev()
+        .map(|level| {
+            stacks
+                .iter()
+                .zip(&widths)
+                .map(|(stack, width)| match stack.get(level) {
+                    Some(label) => format!("{:^width$}", format!("[{}]", label)),
+                    None => " ".repeat(*width)
+                })
+                .collect::<Vec<String>>()
+                .join(" ")
+        })
+        .collect::<Vec<String>>();
+
+    lines.push(widths.iter().enumerate().map(|(i, width)| format!("{:^width$}", i + 1)).collect::<Vec<String>>().join(" "));
+    lines.join("\n")
+}
+
+pub fn get_drawing(data: &str) -> &str { // Just the stacks part of the input
+    data.split_once("\n\n").map_or(data, |(drawing, _)| drawing)
+}
+
+pub fn trace_instructions(stacks: &mut [CrateStack], instructions: &[Move], crane: &mut dyn CrateMover) -> Result<Vec<String>, ExecutionError> { // Drawing after every move, starting with the initial one
+    let mut drawings = vec![render_stacks(stacks)];
+    for (index, instruction) in instructions.iter().enumerate() {
+        execute_instructions(stacks, &[*instruction], crane, ExecutionMode::Strict)
+            .map_err(|err| ExecutionError { instruction: index + 1, ..err })?;
+        drawings.push(render_stacks(stacks));
+    }
+    Ok(drawings)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use super::super::{PUZZLE_INPUT, parse_stacks};
+    use crate::rng::Rng;
+
+    fn random_stacks(rng: &mut Rng) -> Vec<CrateStack> {
+        let labels = ('A'..='Z').chain('0'..='9').collect::<Vec<char>>();
+        (0 .. 1 + rng.below(12))
+            .map(|_| {
+                (0 .. rng.below(6))
+                    .map(|_| (0 .. 1 + rng.below(3)).map(|_| labels[rng.below(labels.len())]).collect::<String>())
+                    .collect::<CrateStack>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rendered_stacks_parse_back() {
+        let mut rng = Rng::new(41);
+        for _ in 0..500 {
+            let stacks = random_stacks(&mut rng);
+            let drawing = render_stacks(&stacks);
+            assert_eq!(parse_stacks(&drawing), Ok(stacks), "\n{}", drawing);
+        }
+    }
+
+    #[test]
+    fn puzzle_drawing_renders_byte_for_byte() {
+        let input = fs::read_to_string(PUZZLE_INPUT).expect("Can't read file");
+        assert_eq!(render_stacks(&parse_stacks(&input).unwrap()), get_drawing(&input));
+    }
+}
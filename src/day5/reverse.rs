@@ -0,0 +1,81 @@
+use std::fs;
+
+use super::{CrateStack, Move, ExecutionMode, ExecutionError, PUZZLE_INPUT, execute_instructions, get_crate_stacks, parse_instructions};
+use super::crane::CrateMover9001;
+
+// The 9001 keeps the order of the block it moves, so putting the same number of crates straight back undoes a move exactly.
+// That only holds if every crate asked for was really there, so everything here runs in strict mode
+
+pub fn invert(instruction: &Move) -> Move {
+    Move { count: instruction.count, from: instruction.to, to: instruction.from }
+}
+
+pub fn inverse_program(program: &[Move]) -> Vec<Move> { // Takes the final stacks back to the starting ones
+    program.iter().rev().map(invert).collect()
+}
+
+pub struct ReversibleRun {
+    stacks: Vec<CrateStack>,
+    program: Vec<Move>,
+    executed: usize // Moves from the start of program currently applied
+}
+
+impl ReversibleRun {
+    pub fn new(stacks: Vec<CrateStack>, program: Vec<Move>) -> ReversibleRun {
+        ReversibleRun { stacks, program, executed: 0 }
+    }
+
+    pub fn stacks(&self) -> &[CrateStack] {
+        &self.stacks
+    }
+
+    pub fn executed(&self) -> usize {
+        self.executed
+    }
+
+    pub fn redo(&mut self) -> Result<bool, ExecutionError> { // Runs the next move, false if there are none left
+        let Some(instruction) = self.program.get(self.executed) else {
+            return Ok(false)
+        };
+        execute_instructions(&mut self.stacks, &[*instruction], &mut CrateMover9001, ExecutionMode::Strict)
+            .map_err(|err| ExecutionError { instruction: self.executed + 1, ..err })?;
+        self.executed += 1;
+        Ok(true)
+    }
+
+    pub fn undo(&mut self) -> bool { // Takes back the last move, false if nothing has run
+        if self.executed == 0 {
+            return false
+        }
+        self.executed -= 1;
+        let inverse = invert(&self.program[self.executed]);
+        execute_instructions(&mut self.stacks, &[inverse], &mut CrateMover9001, ExecutionMode::Strict)
+            .expect("Inverse of a move that ran can always run");
+        true
+    }
+
+    pub fn run_to_end(&mut self) -> Result<(), ExecutionError> {
+        while self.redo()? {}
+        Ok(())
+    }
+
+    pub fn rewind(&mut self) {
+        while self.undo() {}
+    }
+}
+
+pub fn verify_reversible(stacks: &[CrateStack], program: &[Move]) -> Result<(), ExecutionError> { // Forward then backward, panics if it doesn't come back to the start
+    let mut finished = stacks.to_vec();
+    execute_instructions(&mut finished, program, &mut CrateMover9001, ExecutionMode::Strict)?;
+
+    let mut restored = finished;
+    execute_instructions(&mut restored, &inverse_program(program), &mut CrateMover9001, ExecutionMode::Strict)
+        .expect("Inverse program can always run after the program did");
+    assert_eq!(restored, stacks, "Inverse program didn't restore the starting stacks");
+    Ok(())
+}
+
+pub fn verify_puzzle_program() -> Result<(), ExecutionError> {
+    let input = fs::read_to_string(PUZZLE_INPUT).expect("Can't read file");
+    verify_reversible(&get_crate_stacks(&input), &parse_instructions(&input))
+}
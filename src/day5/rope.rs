@@ -0,0 +1,235 @@
+use std::fmt;
+
+use super::{Crate, CrateStack, Move};
+use crate::rng::Rng;
+
+// Stacks kept as implicit treaps (balanced by random priorities, ordered by position) sharing one node arena.
+// A block move splits the top off one tree and joins it onto another, and a 9000 style move just flags the block
+// as reversed, so both cost O(log n) however many crates are moved. Flags are pushed down to children lazily
+
+struct Node {
+    label: Crate,
+    priority: u64,
+    size: usize,    // Crates in this subtree
+    reversed: bool, // Whole subtree is upside down, not yet passed on to the children
+    left: Option<usize>,
+    right: Option<usize>
+}
+
+pub struct RopeStacks {
+    nodes: Vec<Node>,
+    roots: Vec<Option<usize>>, // Bottom of a stack is the leftmost node
+    rng: Rng
+}
+
+impl RopeStacks {
+    pub fn new(stack_count: usize) -> RopeStacks {
+        RopeStacks { nodes: Vec::new(), roots: vec![None; stack_count], rng: Rng::new(2022) }
+    }
+
+    pub fn from_stacks(stacks: &[CrateStack]) -> RopeStacks {
+        let mut ropes = RopeStacks::new(stacks.len());
+        for (index, stack) in stacks.iter().enumerate() {
+            for label in stack {
+                ropes.push(index, label.clone());
+            }
+        }
+        ropes
+    }
+
+    fn size(&self, node: Option<usize>) -> usize {
+        node.map_or(0, |n| self.nodes[n].size)
+    }
+
+    fn update(&mut self, n: usize) {
+        self.nodes[n].size = 1 + self.size(self.nodes[n].left) + self.size(self.nodes[n].right);
+    }
+
+    fn push_down(&mut self, n: usize) {
+        if self.nodes[n].reversed {
+            let node = &mut self.nodes[n];
+            std::mem::swap(&mut node.left, &mut node.right);
+            node.reversed = false;
+            for child in [self.nodes[n].left, self.nodes[n].right].into_iter().flatten() {
+                self.nodes[child].reversed ^= true;
+            }
+        }
+    }
+
+    fn split(&mut self, node: Option<usize>, k: usize) -> (Option<usize>, Option<usize>) { // First k crates, then the rest
+        let Some(n) = node else {
+            return (None, None)
+        };
+        self.push_down(n);
+        let left_size = self.size(self.nodes[n].left);
+        if k <= left_size {
+            let (first, rest) = self.split(self.nodes[n].left, k);
+            self.nodes[n].left = rest;
+            self.update(n);
+            (first, Some(n))
+        }
+        else {
+            let (first, rest) = self.split(self.nodes[n].right, k - left_size - 1);
+            self.nodes[n].right = first;
+            self.update(n);
+            (Some(n), rest)
+        }
+    }
+
+    fn merge(&mut self, bottom: Option<usize>, top: Option<usize>) -> Option<usize> {
+        match (bottom, top) {
+            (None, tree) | (tree, None) => tree,
+            (Some(b), Some(t)) if self.nodes[b].priority > self.nodes[t].priority => {
+                self.push_down(b);
+                self.nodes[b].right = self.merge(self.nodes[b].right, top);
+                self.update(b);
+                Some(b)
+            }
+            (Some(_), Some(t)) => {
+                self.push_down(t);
+                self.nodes[t].left = self.merge(bottom, self.nodes[t].left);
+                self.update(t);
+                Some(t)
+            }
+        }
+    }
+
+    pub fn stack_count(&self) -> usize {
+        self.roots.len()
+    }
+
+    pub fn len(&self, stack: usize) -> usize {
+        self.size(self.roots[stack])
+    }
+
+    pub fn is_empty(&self, stack: usize) -> bool {
+        self.roots[stack].is_none()
+    }
+
+    pub fn push(&mut self, stack: usize, label: Crate) {
+        let priority = self.rng.next();
+        self.nodes.push(Node { label, priority, size: 1, reversed: false, left: None, right: None });
+        let node = Some(self.nodes.len() - 1);
+        self.roots[stack] = self.merge(self.roots[stack], node);
+    }
+
+    pub fn move_crates(&mut self, amount: usize, from: usize, to: usize, reversed: bool) { // Stacks indexed from 0, missing crates are ignored like lift
+        if from == to && !reversed {
+            return
+        }
+        let keep = self.len(from).saturating_sub(amount);
+        let (rest, block) = self.split(self.roots[from], keep);
+        if let Some(b) = block.filter(|_| reversed) {
+            self.nodes[b].reversed ^= true;
+        }
+        self.roots[from] = rest;
+        self.roots[to] = self.merge(self.roots[to], block);
+    }
+
+    pub fn execute(&mut self, instructions: &[Move], reversed: bool) { // reversed for a 9000, kept for a 9001. Same as lenient mode
+        for instruction in instructions {
+            let exists = |stack: usize| stack >= 1 && stack <= self.stack_count();
+            if exists(instruction.from) && exists(instruction.to) {
+                self.move_crates(instruction.count, instruction.from - 1, instruction.to - 1, reversed);
+            }
+        }
+    }
+
+    pub fn top(&self, stack: usize) -> Option<&Crate> { // Follow the rightmost path, keeping track of flags on the way down
+        let mut node = self.roots[stack]?;
+        let mut flipped = false;
+        loop {
+            flipped ^= self.nodes[node].reversed;
+            let next = if flipped { self.nodes[node].left } else { self.nodes[node].right };
+            match next {
+                Some(n) => node = n,
+                None => return Some(&self.nodes[node].label)
+            }
+        }
+    }
+
+    pub fn tops(&self) -> String { // Same as get_top_crates_str
+        (0..self.stack_count()).filter_map(|stack| self.top(stack)).map(|label| label.as_str()).collect()
+    }
+
+    fn collect(&self, node: Option<usize>, flipped: bool, out: &mut CrateStack) { // Depth is O(log n) in expectation
+        let Some(n) = node else {
+            return
+        };
+        let flipped = flipped ^ self.nodes[n].reversed;
+        let (first, second) = if flipped { (self.nodes[n].right, self.nodes[n].left) } else { (self.nodes[n].left, self.nodes[n].right) };
+        self.collect(first, flipped, out);
+        out.push_back(self.nodes[n].label.clone());
+        self.collect(second, flipped, out);
+    }
+
+    pub fn to_stacks(&self) -> Vec<CrateStack> {
+        self.roots
+            .iter()
+            .map(|&root| {
+                let mut stack = CrateStack::with_capacity(self.size(root));
+                self.collect(root, false, &mut stack);
+                stack
+            })
+            .collect()
+    }
+}
+
+impl fmt::Debug for RopeStacks { // Heights and tops only, stacks can hold millions of crates
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let heights = (0..self.stack_count()).map(|stack| self.len(stack)).collect::<Vec<usize>>();
+        f.debug_struct("RopeStacks").field("heights", &heights).field("tops", &self.tops()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{PUZZLE_INPUT, get_crate_stacks, parse_instructions};
+    use super::super::crane::{CrateMover, CrateMover9000, CrateMover9001};
+
+    fn check(ropes: &RopeStacks, deques: &[CrateStack]) {
+        assert_eq!(ropes.to_stacks(), deques);
+        for (stack, deque) in deques.iter().enumerate() {
+            assert_eq!(ropes.len(stack), deque.len());
+            assert_eq!(ropes.top(stack), deque.back());
+        }
+    }
+
+    #[test]
+    fn block_moves_match_cranes() {
+        let mut rng = Rng::new(46);
+        for reversed in [true, false] {
+            for _ in 0..50 {
+                let stack_count = 1 + rng.below(4);
+                let mut deques = vec![CrateStack::new(); stack_count];
+                let mut ropes = RopeStacks::new(stack_count);
+                let mut crane: Box<dyn CrateMover> = if reversed { Box::new(CrateMover9000) } else { Box::new(CrateMover9001) };
+                for step in 0..200 {
+                    let stack = rng.below(stack_count);
+                    if rng.below(4) == 0 {
+                        deques[stack].push_back(step.to_string());
+                        ropes.push(stack, step.to_string());
+                    }
+                    else { // Sometimes onto the same stack and sometimes asking for more crates than there are
+                        let (amount, to) = (rng.below(12), rng.below(stack_count));
+                        crane.move_crates(&mut deques, amount, stack, to);
+                        ropes.move_crates(amount, stack, to, reversed);
+                    }
+                    check(&ropes, &deques);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn puzzle_answers() {
+        let input = std::fs::read_to_string(PUZZLE_INPUT).expect("Can't read file");
+        let (stacks, instructions) = (get_crate_stacks(&input), parse_instructions(&input));
+        for (reversed, answer) in [(true, "NTWZZWHFV"), (false, "BRZGFVBTJ")] {
+            let mut ropes = RopeStacks::from_stacks(&stacks);
+            ropes.execute(&instructions, reversed);
+            assert_eq!(ropes.tops(), answer);
+        }
+    }
+}
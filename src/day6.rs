@@ -1,50 +1,257 @@
-use std::{fs, collections::{VecDeque, HashSet}};
+use std::{fs, collections::{VecDeque, HashMap}, hash::Hash, io::{self, Read, BufRead, BufReader}};
+
+use crate::{bench::timed, rng::Rng};
+
+pub mod frames;
 
 const PUZZLE_INPUT: &str = "data/stream.txt";
+const PACKET_MARKER_SIZE: usize = 4;
+const MESSAGE_MARKER_SIZE: usize = 14;
+const READ_CHUNK: usize = 64 * 1024;
 
-struct Buffer<T> {
-    contents: VecDeque<T>,
+struct Buffer<T> { // Tracks the run of distinct symbols ending at the newest one, so each push is O(1)
     size: usize,
     counter: usize,
-    unique_set: HashSet<T>
+    run_start: usize,               // Symbols pushed before the current distinct run
+    last_seen: HashMap<T, usize>    // Symbol to the counter value when it was last pushed
 }
 
-impl<T: std::cmp::Eq + std::hash::Hash + Copy> Buffer<T> {
-    fn push(&mut self, value: &T) { // Push newest char to back and pop oldest from front if buffer is fully sized
-        if self.contents.len() == self.size { 
-            let removed_value = self.contents.pop_front().unwrap();
-            if !self.contents.contains(&removed_value) { // remove char from hashset if char is not anywhere else in buffer
-                self.unique_set.remove(&removed_value);
-            } 
-        }
-        self.contents.push_back(*value);
-        self.unique_set.insert(*value);
+impl<T: Eq + Hash + Copy> Buffer<T> {
+    fn push(&mut self, value: &T) { // A repeat inside the run means nothing up to its earlier copy can be part of a marker, so skip past it
         self.counter += 1;
+        if let Some(previous) = self.last_seen.insert(*value, self.counter) {
+            self.run_start = self.run_start.max(previous);
+        }
     }
 
-    fn fully_unique(&self) -> bool { // If buffer is full and hashset is same size of buffer, marker has been found
-        self.unique_set.len() == self.size && self.unique_set.len() == self.contents.len()
+    fn run_len(&self) -> usize {
+        self.counter - self.run_start
     }
 
-    fn new(size: usize) -> Buffer<T> { 
-        Buffer { contents: VecDeque::new(), size, counter: 0, unique_set: HashSet::new() }
+    fn fully_unique(&self) -> bool { // Last size symbols are all different
+        self.run_len() >= self.size
+    }
+
+    fn new(size: usize) -> Buffer<T> {
+        Buffer { size, counter: 0, run_start: 0, last_seen: HashMap::new() }
     }
 }
 
-fn find_marker(data: &str, marker_size: usize) -> Option<usize> {
-    let mut buffer = Buffer::<char>::new(marker_size);
-    for char in data.chars() {
-        buffer.push(&char);    
+pub fn find_marker_in<T: Eq + Hash + Copy>(symbols: impl IntoIterator<Item = T>, marker_size: usize) -> Option<usize> { // Count of symbols up to and including the end of the first marker
+    let mut buffer = Buffer::<T>::new(marker_size);
+    for symbol in symbols {
+        buffer.push(&symbol);
         if buffer.fully_unique() {
             return Some(buffer.counter)
         }
-    }            
+    }
+    None
+}
+
+pub struct Markers<I: Iterator> { // Every position where the last marker_size symbols are all different
+    symbols: I,
+    buffer: Buffer<I::Item>
+}
+
+impl<I: Iterator> Iterator for Markers<I> where I::Item: Eq + Hash + Copy {
+    type Item = usize; // Same counting as find_marker
+
+    fn next(&mut self) -> Option<usize> {
+        for symbol in self.symbols.by_ref() {
+            self.buffer.push(&symbol);
+            if self.buffer.fully_unique() {
+                return Some(self.buffer.counter)
+            }
+        }
+        None
+    }
+}
+
+pub fn markers<I: IntoIterator>(symbols: I, marker_size: usize) -> Markers<I::IntoIter> where I::Item: Eq + Hash + Copy {
+    Markers { symbols: symbols.into_iter(), buffer: Buffer::new(marker_size) }
+}
+
+pub fn first_markers<T: Eq + Hash + Copy>(symbols: impl IntoIterator<Item = T>, marker_sizes: &[usize]) -> Vec<Option<usize>> { // One pass for all sizes, in the order given
+    let mut found = vec![None; marker_sizes.len()];
+    let mut remaining = marker_sizes.len();
+    let mut buffer = Buffer::<T>::new(0);
+    for symbol in symbols {
+        if remaining == 0 {
+            break
+        }
+        buffer.push(&symbol);
+        for (size, first) in marker_sizes.iter().zip(found.iter_mut()) { // A run of distinct symbols is a marker for every size up to its length
+            if first.is_none() && buffer.run_len() >= *size {
+                *first = Some(buffer.counter);
+                remaining -= 1;
+            }
+        }
+    }
+    found
+}
+
+pub fn find_marker(data: &str, marker_size: usize) -> Option<usize> {
+    find_marker_in(data.chars(), marker_size)
+}
+
+pub struct StreamScanner { // Byte version of Buffer with a table instead of a HashMap, fed a chunk at a time
+    marker_size: usize,
+    counter: usize, // Bytes scanned so far, across every chunk
+    run_start: usize,
+    last_seen: [usize; 256]
+}
+
+impl StreamScanner {
+    pub fn new(marker_size: usize) -> StreamScanner {
+        StreamScanner { marker_size, counter: 0, run_start: 0, last_seen: [0; 256] }
+    }
+
+    pub fn offset(&self) -> usize {
+        self.counter
+    }
+
+    pub fn scan(&mut self, chunk: &[u8]) -> (usize, Option<usize>) { // Bytes of chunk used and the absolute end of a marker. Stops just after a marker so the rest can be fed back in
+        for (index, &byte) in chunk.iter().enumerate() {
+            self.counter += 1;
+            self.run_start = self.run_start.max(self.last_seen[byte as usize]);
+            self.last_seen[byte as usize] = self.counter;
+            if self.counter - self.run_start >= self.marker_size {
+                return (index + 1, Some(self.counter))
+            }
+        }
+        (chunk.len(), None)
+    }
+}
+
+pub fn find_marker_bytes(data: &[u8], marker_size: usize) -> Option<usize> {
+    StreamScanner::new(marker_size).scan(data).1
+}
+
+pub fn find_marker_buffered(mut reader: impl BufRead, marker_size: usize) -> io::Result<Option<usize>> { // Reads no further than the end of the marker
+    let mut scanner = StreamScanner::new(marker_size);
+    loop {
+        let chunk = reader.fill_buf()?;
+        if chunk.is_empty() {
+            return Ok(None)
+        }
+        let (used, marker) = scanner.scan(chunk);
+        reader.consume(used);
+        if marker.is_some() {
+            return Ok(marker)
+        }
+    }
+}
+
+pub fn find_marker_reader(reader: impl Read, marker_size: usize) -> io::Result<Option<usize>> {
+    find_marker_buffered(BufReader::with_capacity(READ_CHUNK, reader), marker_size)
+}
+
+pub fn all_markers_buffered(mut reader: impl BufRead, marker_size: usize) -> io::Result<Vec<usize>> { // Every marker end in the stream, like markers
+    let mut scanner = StreamScanner::new(marker_size);
+    let mut found = Vec::<usize>::new();
+    loop {
+        let chunk = reader.fill_buf()?;
+        if chunk.is_empty() {
+            return Ok(found)
+        }
+        let (used, marker) = scanner.scan(chunk);
+        reader.consume(used);
+        found.extend(marker);
+    }
+}
+
+pub fn all_markers_reader(reader: impl Read, marker_size: usize) -> io::Result<Vec<usize>> {
+    all_markers_buffered(BufReader::with_capacity(READ_CHUNK, reader), marker_size)
+}
+
+fn find_marker_rescanning(data: &str, marker_size: usize) -> Option<usize> { // Original approach, checks the whole window for every char
+    let mut window = VecDeque::<char>::with_capacity(marker_size + 1);
+    for (index, char) in data.chars().enumerate() {
+        window.push_back(char);
+        if window.len() > marker_size {
+            window.pop_front();
+        }
+        if window.len() == marker_size && window.iter().enumerate().all(|(i, c)| !window.range(i + 1 ..).any(|other| other == c)) {
+            return Some(index + 1)
+        }
+    }
     None
 }
 
-pub fn solve() -> usize {
+pub fn generate_stream(length: usize, marker_size: usize, seed: u64) -> String { // Random letters with no marker until the end, marker_size up to 26
+    let marker_size = marker_size.clamp(2, 26);
+    let letters = ('a'..='z').take(marker_size).collect::<Vec<char>>();
+    let mut rng = Rng::new(seed);
+
+    let mut stream = (0 .. length.saturating_sub(marker_size))
+        .map(|_| letters[rng.below(marker_size - 1)]) // One letter short, so no window can be all different
+        .collect::<String>();
+    stream.extend(letters.iter().rev());
+    stream
+}
+
+pub fn benchmark_markers(length: usize, marker_size: usize) -> Result<(), &'static str> {
+    let data = generate_stream(length, marker_size, 2022);
+    println!("{} byte stream, marker size {}", data.len(), marker_size);
+
+    let rescanning = timed("Rescanning", || find_marker_rescanning(&data, marker_size));
+    let buffered = timed("Buffer", || find_marker(&data, marker_size));
+    let table = timed("Byte table", || find_marker_bytes(data.as_bytes(), marker_size));
+
+    if rescanning != buffered || rescanning != table {
+        return Err("Marker finders disagree")
+    }
+    Ok(())
+}
+
+pub fn solve() -> (usize, usize) { // Start of packet and start of message markers
     let input = fs::read_to_string(PUZZLE_INPUT).expect("Can't read file");
-    //find_marker(&input, 4)
-    find_marker(&input, 14).unwrap()
+    match first_markers(input.chars(), &[PACKET_MARKER_SIZE, MESSAGE_MARKER_SIZE])[..] {
+        [Some(packet), Some(message)] => (packet, message),
+        _ => panic!("Stream has no marker")
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window_markers(symbols: &[u8], marker_size: usize) -> Vec<usize> { // Every window checked from scratch
+        (marker_size ..= symbols.len())
+            .filter(|&end| {
+                let window = &symbols[end - marker_size .. end];
+                window.iter().enumerate().all(|(i, symbol)| !window[i + 1 ..].contains(symbol))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn markers_match_window_scan() {
+        let mut rng = Rng::new(47);
+        for _ in 0..500 {
+            let letters = 1 + rng.below(10); // Few letters so markers come and go
+            let length = rng.below(120);
+            let stream = (0..length).map(|_| b'a' + rng.below(letters) as u8).collect::<Vec<u8>>();
+            let sizes = (1..=8).collect::<Vec<usize>>();
+
+            for &size in &sizes {
+                let expected = window_markers(&stream, size);
+                assert_eq!(markers(stream.iter().copied(), size).collect::<Vec<usize>>(), expected);
+                assert_eq!(find_marker_in(stream.iter().copied(), size), expected.first().copied());
+                assert_eq!(find_marker_bytes(&stream, size), expected.first().copied());
+                let chunk = 1 + rng.below(8); // Markers can straddle chunks
+                assert_eq!(all_markers_buffered(BufReader::with_capacity(chunk, stream.as_slice()), size).unwrap(), expected);
+                assert_eq!(find_marker_buffered(BufReader::with_capacity(chunk, stream.as_slice()), size).unwrap(), expected.first().copied());
+            }
+            let expected = sizes.iter().map(|&size| window_markers(&stream, size).first().copied()).collect::<Vec<Option<usize>>>();
+            assert_eq!(first_markers(stream.iter().copied(), &sizes), expected);
+        }
+    }
+
+    #[test]
+    fn puzzle_answers() {
+        assert_eq!(find_marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb", PACKET_MARKER_SIZE), Some(7));
+        assert_eq!(find_marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb", MESSAGE_MARKER_SIZE), Some(19));
+        assert_eq!(solve(), (1598, 2414));
+    }
+}
@@ -0,0 +1,178 @@
+use std::{fs, fmt, ops::Range};
+
+use super::{PUZZLE_INPUT, PACKET_MARKER_SIZE, MESSAGE_MARKER_SIZE, find_marker_bytes};
+
+// Splits a datastream into frames, each a marker followed by everything up to the next marker. Searching starts
+// again after each marker ends, so markers never overlap. Every message marker also contains packet markers, so a
+// message marker wins over any packet marker it overlaps. Offsets are 0-based byte positions in the stream
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarkerKind {
+    Packet,
+    Message
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub kind: MarkerKind,
+    pub offset: usize, // Where the marker starts
+    pub marker: Vec<u8>,
+    pub payload: Vec<u8>
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedStream {
+    pub preamble: Vec<u8>, // Anything before the first marker
+    pub frames: Vec<Frame>
+}
+
+impl Frame {
+    pub fn marker_range(&self) -> Range<usize> {
+        self.offset .. self.offset + self.marker.len()
+    }
+
+    pub fn payload_range(&self) -> Range<usize> {
+        self.marker_range().end .. self.marker_range().end + self.payload.len()
+    }
+
+    pub fn hexdump(&self) -> String { // Marker and payload together, 16 bytes a line, with stream offsets on the left
+        hexdump(&[self.marker.as_slice(), self.payload.as_slice()].concat(), self.offset)
+    }
+}
+
+pub fn hexdump(bytes: &[u8], offset: usize) -> String {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(line, chunk)| {
+            let hex = (0..16)
+                .map(|i| chunk.get(i).map_or("  ".to_string(), |byte| format!("{:02x}", byte)))
+                .collect::<Vec<String>>();
+            let text = chunk.iter().map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' }).collect::<String>();
+            format!("{:08x}  {}  {}  |{}|", offset + line * 16, hex[..8].join(" "), hex[8..].join(" "), text)
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { // Non printable bytes are escaped
+        let kind = match self.kind {
+            MarkerKind::Packet => "Packet",
+            MarkerKind::Message => "Message"
+        };
+        write!(f, "{} {}..{} \"{}\", {} byte payload \"{}\"", kind, self.marker_range().start, self.marker_range().end,
+            self.marker.escape_ascii(), self.payload.len(), self.payload.escape_ascii())
+    }
+}
+
+impl fmt::Display for DecodedStream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} byte preamble, {} frames", self.preamble.len(), self.frames.len())?;
+        for frame in &self.frames {
+            writeln!(f, "{}", frame)?;
+        }
+        Ok(())
+    }
+}
+
+fn marker_from(data: &[u8], from: usize, size: usize) -> Option<Range<usize>> {
+    let end = from + find_marker_bytes(&data[from..], size)?;
+    Some(end - size .. end)
+}
+
+pub fn decode_frames(data: &[u8]) -> DecodedStream {
+    let mut markers = Vec::<(MarkerKind, Range<usize>)>::new();
+    let mut position = 0;
+    let mut message = marker_from(data, 0, MESSAGE_MARKER_SIZE); // Next one is still next until we pass its start, so each byte is only searched once
+    while let Some(packet) = marker_from(data, position, PACKET_MARKER_SIZE) {
+        if message.as_ref().is_some_and(|m| m.start < position) {
+            message = marker_from(data, position, MESSAGE_MARKER_SIZE);
+        }
+        let next = match &message {
+            Some(m) if m.start < packet.end => (MarkerKind::Message, m.clone()),
+            _ => (MarkerKind::Packet, packet)
+        };
+        position = next.1.end;
+        markers.push(next);
+    }
+
+    let preamble = data[.. markers.first().map_or(data.len(), |(_, marker)| marker.start)].to_vec();
+    let frames = markers
+        .iter()
+        .enumerate()
+        .map(|(i, (kind, marker))| {
+            let payload_end = markers.get(i + 1).map_or(data.len(), |(_, next)| next.start);
+            Frame { kind: *kind, offset: marker.start, marker: data[marker.clone()].to_vec(), payload: data[marker.end .. payload_end].to_vec() }
+        })
+        .collect();
+    DecodedStream { preamble, frames }
+}
+
+pub fn get_frames() -> DecodedStream {
+    decode_frames(&fs::read(PUZZLE_INPUT).expect("Can't read file"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn frame(kind: MarkerKind, offset: usize, marker: &str, payload: &str) -> Frame {
+        Frame { kind, offset, marker: marker.as_bytes().to_vec(), payload: payload.as_bytes().to_vec() }
+    }
+
+    #[test]
+    fn message_marker_wins_over_packet_marker() { // "bcde" is a packet marker inside the message marker
+        let decoded = decode_frames(b"bbbcdefghijklmnowwwxyzzz");
+        assert_eq!(decoded.preamble, b"bb");
+        assert_eq!(decoded.frames, vec![
+            frame(MarkerKind::Message, 2, "bcdefghijklmno", "ww"),
+            frame(MarkerKind::Packet, 18, "wxyz", "zz")
+        ]);
+    }
+
+    #[test]
+    fn packet_marker_before_message_marker() { // Message search is kept from the start until the packet passes it
+        let decoded = decode_frames(b"abcdaaaabcdefghijklmnopp");
+        assert_eq!(decoded.preamble, b"");
+        assert_eq!(decoded.frames, vec![
+            frame(MarkerKind::Packet, 0, "abcd", "aaa"),
+            frame(MarkerKind::Message, 7, "abcdefghijklmn", "opp")
+        ]);
+        assert_eq!(decoded.frames[1].marker_range(), 7..21);
+        assert_eq!(decoded.frames[1].payload_range(), 21..24);
+    }
+
+    #[test]
+    fn stream_without_markers() {
+        for data in [&b""[..], b"abcabcabc", b"aaaa"] {
+            assert_eq!(decode_frames(data), DecodedStream { preamble: data.to_vec(), frames: Vec::new() });
+        }
+    }
+
+    #[test]
+    fn frames_cover_the_stream() {
+        let mut rng = Rng::new(50);
+        for _ in 0..300 {
+            let letters = 3 + rng.below(14);
+            let data = (0..rng.below(200)).map(|_| b'a' + rng.below(letters) as u8).collect::<Vec<u8>>();
+            let decoded = decode_frames(&data);
+
+            let mut rebuilt = decoded.preamble.clone();
+            for frame in &decoded.frames {
+                assert_eq!(frame.marker_range().start, rebuilt.len());
+                let size = match frame.kind {
+                    MarkerKind::Packet => PACKET_MARKER_SIZE,
+                    MarkerKind::Message => MESSAGE_MARKER_SIZE
+                };
+                assert_eq!(frame.marker.len(), size);
+                assert_eq!(find_marker_bytes(&frame.marker, size), Some(size));
+                assert_eq!(find_marker_bytes(&frame.payload, PACKET_MARKER_SIZE), None); // Nothing skipped in the payload
+                rebuilt.extend(&frame.marker);
+                rebuilt.extend(&frame.payload);
+            }
+            assert_eq!(rebuilt, data);
+        }
+    }
+}
@@ -17,8 +17,14 @@ mod day7;
 mod day8;
 mod day9;
 mod day10;
+mod bench;
+mod rng;
 
 fn main() {
+    if std::env::args().nth(1).as_deref() == Some("bench") {
+        bench::run_all().unwrap();
+        return
+    }
     // println!("{}", get_max_calories());
     // println!("{}", get_top_n_max_calories(3));
     // println!("{:?}", get_final_score());
@@ -0,0 +1,20 @@
+// Small seeded generator shared by the bots and the input generators, so runs can be replayed
+
+pub struct Rng(u64); // xorshift64, fine for games and test data but not for anything that needs real randomness
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed.max(1)) // xorshift gets stuck on 0
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn below(&mut self, bound: usize) -> usize { // 0 .. bound, bound has to be at least 1
+        (self.next() % bound as u64) as usize
+    }
+}